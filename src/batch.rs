@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use crate::{parallel, simplify::Simplify, Tf, TfVar};
+
+/// A result of a batch, with the time it took to compute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timed<T>
+{
+    pub value: T,
+    pub elapsed: Duration
+}
+
+/// Applies `f` to every item, spread across the threads set with [set_threads](crate::parallel::set_threads),
+/// returning the results in order.
+///
+/// Each item is computed on a single thread, so the arithmetic within it runs serially.
+pub fn batch<T, R, F>(items: &[T], f: F) -> Vec<Timed<R>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync
+{
+    parallel::map_chunks(items.len(), 1, |i| items[i].iter()
+        .map(|item| {
+            let start = Instant::now();
+            let value = f(item);
+            Timed {value, elapsed: start.elapsed()}
+        }).collect::<Vec<_>>()
+    ).into_iter()
+        .flatten()
+        .collect()
+}
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    /// Simplifies every transfer function in a [batch](batch).
+    pub fn simplify_batch(h: &[Self]) -> Vec<Timed<Self>>
+    {
+        batch(h, |h| {
+            let mut h = h.clone();
+            h.simplify();
+            h
+        })
+    }
+
+    /// Formats the numerator and denominator coefficients of every transfer function in a [batch](batch).
+    pub fn coefficients_batch(h: &[Self]) -> Vec<Timed<(Vec<String>, Vec<String>)>>
+    {
+        batch(h, |h| (
+            h.0.0.iter().map(|b| b.to_string()).collect(),
+            h.1.0.iter().map(|a| a.to_string()).collect()
+        ))
+    }
+}
+
+impl Tf<{TfVar::S}>
+{
+    /// The [bilinear transform](Tf::bilinear_transform) of every transfer function in a [batch](batch).
+    pub fn bilinear_transform_batch(h: &[Self]) -> Vec<Timed<Tf<{TfVar::Z}>>>
+    {
+        batch(h, |h| h.clone().bilinear_transform())
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use num::Float;
+
+use crate::{coefficient::Coefficient, polynomial::Polynomial, Tf, TfVar};
+
+/// The biquads of the Audio EQ Cookbook by Robert Bristow-Johnson.
+///
+/// They are in terms of the symbols `Q`, `A` (the amplitude, `10^(gain/40)`) and `sqrt_A`,
+/// and in the z-domain also `cos_w0` and `alpha` (`sin(w0)/(2*Q)`).
+/// See [biquad_syms](biquad_syms) for their values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biquad
+{
+    Lowpass,
+    Highpass,
+    /// Bandpass with a peak gain of 0 dB.
+    Bandpass,
+    Notch,
+    Allpass,
+    Peaking,
+    LowShelf,
+    HighShelf
+}
+
+fn c(sym: &'static str) -> Coefficient
+{
+    Coefficient::from(sym)
+}
+
+fn n(i: i128) -> Coefficient
+{
+    Coefficient::from(i)
+}
+
+impl Tf<{TfVar::S}>
+{
+    /// The analog prototype of a cookbook biquad, with the frequency `omega` in radians per second.
+    ///
+    /// With `omega = 2*rate*tan(w0/2)`, its bilinear transform equals the z-domain biquad.
+    pub fn biquad(biquad: Biquad, omega: Coefficient) -> Self
+    {
+        let q_inv = n(1)/c("Q");
+        let p = |c: [Coefficient; 3]| Polynomial(c.to_vec());
+        let den = p([n(1), q_inv.clone(), n(1)]);
+
+        let h = match biquad
+        {
+            Biquad::Lowpass => Tf(p([n(1), n(0), n(0)]), den),
+            Biquad::Highpass => Tf(p([n(0), n(0), n(1)]), den),
+            Biquad::Bandpass => Tf(p([n(0), q_inv, n(0)]), den),
+            Biquad::Notch => Tf(p([n(1), n(0), n(1)]), den),
+            Biquad::Allpass => Tf(p([n(1), -q_inv, n(1)]), den),
+            Biquad::Peaking => Tf(
+                p([n(1), c("A")*q_inv.clone(), n(1)]),
+                p([n(1), q_inv/c("A"), n(1)])
+            ),
+            Biquad::LowShelf => Tf(
+                p([c("A")*c("A"), c("A")*c("sqrt_A")*q_inv.clone(), c("A")]),
+                p([n(1), c("sqrt_A")*q_inv, c("A")])
+            ),
+            Biquad::HighShelf => Tf(
+                p([c("A"), c("A")*c("sqrt_A")*q_inv.clone(), c("A")*c("A")]),
+                p([c("A"), c("sqrt_A")*q_inv, n(1)])
+            )
+        };
+        h.scale_frequency(omega)
+    }
+}
+
+impl Tf<{TfVar::Z}>
+{
+    /// The z-domain cookbook biquad.
+    pub fn biquad(biquad: Biquad) -> Self
+    {
+        let cos_w0 = c("cos_w0");
+        let alpha = c("alpha");
+        let a = c("A");
+        let sqrt_a_alpha = c("sqrt_A")*alpha.clone()*n(2);
+        let den = [n(1) + alpha.clone(), cos_w0.clone()*n(-2), n(1) - alpha.clone()];
+
+        let (b, a) = match biquad
+        {
+            Biquad::Lowpass => ([(n(1) - cos_w0.clone())/n(2), n(1) - cos_w0.clone(), (n(1) - cos_w0)/n(2)], den),
+            Biquad::Highpass => ([(n(1) + cos_w0.clone())/n(2), -(n(1) + cos_w0.clone()), (n(1) + cos_w0)/n(2)], den),
+            Biquad::Bandpass => ([alpha.clone(), n(0), -alpha], den),
+            Biquad::Notch => ([n(1), cos_w0*n(-2), n(1)], den),
+            Biquad::Allpass => ([n(1) - alpha.clone(), cos_w0*n(-2), n(1) + alpha], den),
+            Biquad::Peaking => (
+                [n(1) + alpha.clone()*a.clone(), cos_w0.clone()*n(-2), n(1) - alpha.clone()*a.clone()],
+                [n(1) + alpha.clone()/a.clone(), cos_w0*n(-2), n(1) - alpha/a]
+            ),
+            Biquad::LowShelf => {
+                let ap1 = a.clone() + n(1);
+                let am1 = a.clone() - n(1);
+                (
+                    [
+                        a.clone()*(ap1.clone() - am1.clone()*cos_w0.clone() + sqrt_a_alpha.clone()),
+                        a.clone()*(am1.clone() - ap1.clone()*cos_w0.clone())*n(2),
+                        a*(ap1.clone() - am1.clone()*cos_w0.clone() - sqrt_a_alpha.clone())
+                    ],
+                    [
+                        ap1.clone() + am1.clone()*cos_w0.clone() + sqrt_a_alpha.clone(),
+                        (am1.clone() + ap1.clone()*cos_w0.clone())*n(-2),
+                        ap1 + am1*cos_w0 - sqrt_a_alpha
+                    ]
+                )
+            },
+            Biquad::HighShelf => {
+                let ap1 = a.clone() + n(1);
+                let am1 = a.clone() - n(1);
+                (
+                    [
+                        a.clone()*(ap1.clone() + am1.clone()*cos_w0.clone() + sqrt_a_alpha.clone()),
+                        a.clone()*(am1.clone() + ap1.clone()*cos_w0.clone())*n(-2),
+                        a*(ap1.clone() + am1.clone()*cos_w0.clone() - sqrt_a_alpha.clone())
+                    ],
+                    [
+                        ap1.clone() - am1.clone()*cos_w0.clone() + sqrt_a_alpha.clone(),
+                        (am1.clone() - ap1.clone()*cos_w0.clone())*n(2),
+                        ap1 - am1*cos_w0 - sqrt_a_alpha
+                    ]
+                )
+            }
+        };
+        Tf::iir(&b, &a)
+    }
+}
+
+/// The values of the cookbook symbols for the frequency `f0` at the sample rate `rate`, both in Hz, and the gain in dB.
+///
+/// Also binds `rate` and `omega`, the prewarped analog frequency, so that the bilinear transform of the analog biquad can be computed.
+pub fn biquad_syms<F>(f0: F, rate: F, q: F, gain: F) -> HashMap<&'static str, F>
+where
+    F: Float
+{
+    let two = F::one() + F::one();
+    let w0 = F::from(std::f64::consts::TAU).unwrap()*f0/rate;
+    let a = F::from(10.0).unwrap().powf(gain/F::from(40.0).unwrap());
+    HashMap::from([
+        ("Q", q),
+        ("A", a),
+        ("sqrt_A", a.sqrt()),
+        ("w0", w0),
+        ("cos_w0", w0.cos()),
+        ("alpha", w0.sin()/(two*q)),
+        ("rate", rate),
+        ("omega", two*rate*(w0/two).tan())
+    ])
+}
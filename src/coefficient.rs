@@ -9,7 +9,9 @@ use num::traits::Inv;
 use num::{Float, One, Zero, Integer, BigInt, Signed, ToPrimitive};
 
 use crate::Int;
+use crate::weight::Weight;
 use crate::compute::Compute;
+use crate::context;
 use crate::is_neg::IsNeg;
 use crate::partial_one::PartialOne;
 use crate::partial_zero::PartialZero;
@@ -18,8 +20,8 @@ use crate::simplify::Simplify;
 use crate::sym::Sym;
 use crate::weighted_sum::WeightedSum;
 
-#[derive(PartialEq, Eq, Clone, Debug)]
-pub struct Coefficient(pub WeightedSum<PoweredProduct<Sym, Int>, Int>);
+#[derive(PartialEq, Clone, Debug)]
+pub struct Coefficient(pub WeightedSum<PoweredProduct<Sym, Int>, Weight>);
 
 impl Coefficient
 {
@@ -67,17 +69,185 @@ impl Coefficient
     }
 }
 
+impl Coefficient
+{
+    /// Removes zero terms, zero powers and unit symbols, merging the terms that become equal.
+    pub(crate) fn trim(&self) -> Coefficient
+    {
+        let mut terms = BTreeMap::<PoweredProduct<Sym, Int>, Weight>::new();
+        for (e, &w) in self.0.0.iter()
+        {
+            if Zero::is_zero(&w) || PartialZero::is_zero(e)
+            {
+                continue
+            }
+            let mut e = e.clone();
+            e.0.retain(|s, p| *p != 0 && !PartialOne::is_one(s));
+            *terms.entry(e).or_insert(Zero::zero()) += w;
+        }
+        terms.retain(|_, w| !Zero::is_zero(w));
+        Coefficient(WeightedSum(terms))
+    }
+}
+
+impl Coefficient
+{
+    /// Differentiates with respect to the symbol `sym`.
+    pub fn diff(&self, sym: Sym) -> Coefficient
+    {
+        let mut y = Coefficient(WeightedSum(BTreeMap::new()));
+        for (e, w) in self.0.0.iter()
+        {
+            if let Some(&p) = e.0.get(&sym)
+            {
+                if p == 0
+                {
+                    continue
+                }
+                let mut e = e.clone();
+                if p == 1
+                {
+                    e.0.remove(&sym);
+                }
+                else
+                {
+                    e.0.insert(sym, p - 1);
+                }
+                let mut term = Coefficient(WeightedSum(BTreeMap::new()));
+                term.0.0.insert(e, *w*Weight::from(p));
+                y += term;
+            }
+        }
+        y
+    }
+}
+
+impl Coefficient
+{
+    /// Collects the terms by their power of the symbol `sym`, removing `sym` from each term.
+    pub fn collect(&self, sym: Sym) -> BTreeMap<Int, Coefficient>
+    {
+        let mut powers = BTreeMap::<Int, Coefficient>::new();
+        for (e, w) in self.0.0.iter()
+        {
+            let mut e = e.clone();
+            let p = e.0.remove(&sym).unwrap_or(0);
+            let mut term = Coefficient(WeightedSum(BTreeMap::new()));
+            term.0.0.insert(e, *w);
+            match powers.get_mut(&p)
+            {
+                Some(c) => *c += term,
+                None => {powers.insert(p, term);}
+            }
+        }
+        powers
+    }
+
+    /// Binds the symbols in `syms` to numbers, folding them into the weights and keeping the remaining symbols.
+    ///
+    /// Weights stay exact integers as long as the bound factors of a term are.
+    pub fn partial_eval<F>(&self, syms: HashMap<&'static str, F>) -> Coefficient
+    where
+        F: Float
+    {
+        let mut y = <Coefficient as Zero>::zero();
+        for (e, w) in self.0.0.iter()
+        {
+            let mut e = e.clone();
+            let mut x = 1.0;
+            let mut bound = false;
+            e.0.retain(|sym, p| match syms.get(sym.0).and_then(|v| v.to_f64())
+            {
+                Some(v) => {
+                    x *= v.powi(*p as i32);
+                    bound = true;
+                    false
+                },
+                None => true
+            });
+            let w = if !bound
+            {
+                *w
+            }
+            else if x.fract() == 0.0 && x.abs() < (1u64 << f64::MANTISSA_DIGITS) as f64
+            {
+                *w*Weight::from(x as Int)
+            }
+            else
+            {
+                *w*Weight::from(x)
+            };
+            let mut term = Coefficient(WeightedSum(BTreeMap::new()));
+            term.0.0.insert(e, w);
+            y += term;
+        }
+        y
+    }
+
+    /// Substitutes `value` for the symbol `sym`.
+    ///
+    /// Returns a numerator and a denominator, since negative powers of `sym` turn into negative powers of `value`.
+    /// The denominator is one if `sym` only appears with non-negative powers.
+    pub fn substitute(&self, sym: Sym, value: &Coefficient) -> (Coefficient, Coefficient)
+    {
+        let powers = self.collect(sym);
+        let p_min = powers.keys()
+            .next()
+            .map(|&p| p.min(0))
+            .unwrap_or(0);
+        let pow = |p: Int| (0..p).fold(<Coefficient as One>::one(), |y, _| y*value.clone());
+
+        let mut num = <Coefficient as Zero>::zero();
+        for (p, c) in powers
+        {
+            num += c*pow(p - p_min);
+        }
+        (num, pow(-p_min))
+    }
+}
+
+impl Coefficient
+{
+    /// The sum of the products of the pairs.
+    pub(crate) fn dot(pairs: &[(&Coefficient, &Coefficient)]) -> Coefficient
+    {
+        if !context::report_terms(pairs.iter().map(|(a, b)| a.0.0.len()*b.0.0.len()).sum())
+        {
+            return Zero::zero()
+        }
+        pairs.iter()
+            .map(|&(a, b)| Coefficient(&a.0*&b.0))
+            .sum()
+    }
+}
+
 impl Sum<Coefficient> for Coefficient
 {
     fn sum<I: Iterator<Item = Coefficient>>(mut iter: I) -> Self
     {
+        // Cancelled terms are dropped as they go, so that the sum is only simplified once
         let mut sum = iter.next().unwrap_or(PartialZero::zero());
-
         for e in iter
         {
-            sum += e;
+            for (e, w) in e.0.0
+            {
+                match sum.0.0.get_mut(&e)
+                {
+                    Some(m) => {
+                        *m += w;
+                        if Zero::is_zero(m)
+                        {
+                            sum.0.0.remove(&e);
+                        }
+                    },
+                    None => if !Zero::is_zero(&w)
+                    {
+                        sum.0.0.insert(e, w);
+                    }
+                }
+            }
         }
-
+        sum.0.simplify();
         sum
     }
 }
@@ -172,7 +342,20 @@ impl MulAssign for Coefficient
 {
     fn mul_assign(&mut self, rhs: Self)
     {
-        self.0 = &self.0*&rhs.0
+        *self = &*self*&rhs
+    }
+}
+impl Mul for &Coefficient
+{
+    type Output = Coefficient;
+
+    fn mul(self, rhs: Self) -> Self::Output
+    {
+        if !context::report_terms(self.0.0.len()*rhs.0.0.len())
+        {
+            return Zero::zero()
+        }
+        Coefficient(&self.0*&rhs.0)
     }
 }
 impl<Rhs> Mul<Rhs> for Coefficient
@@ -268,7 +451,7 @@ impl Display for Coefficient
             }
         }
 
-        let sum: Vec<(PoweredProduct<Sym, i128>, i128)> = self.0.0.iter()
+        let sum: Vec<(PoweredProduct<Sym, Int>, Weight)> = self.0.0.iter()
             .map(|(e, w)| (e.clone()/common_coeffs.clone(), *w))
             .collect();
 
@@ -321,7 +504,8 @@ impl Display for Coefficient
 
         let mut first = true;
         
-        if sum.len() == 1 && PartialOne::is_one(&sum.first().unwrap().0) && !first_mul_common
+        // A single term of the common factor alone, with the powers left in the term all zero
+        if !first_mul_common && matches!(&sum[..], [(e, w)] if e.0.values().all(|p| *p == 0) && One::is_one(w))
         {
             return Ok(())
         }
@@ -362,7 +546,7 @@ impl Display for Coefficient
 
             if !PartialOne::is_one(&w_abs)
             {
-                write!(f, "{:.1}", w_abs.to_f64().unwrap())?;
+                write!(f, "{}", w_abs)?;
                 first_mul = false;
             }
             
@@ -447,7 +631,30 @@ impl From<Int> for Coefficient
         let mut sum = PartialZero::zero();
         let product: PoweredProduct<Sym, Int> = PartialOne::one();
         sum += product;
-        sum *= i;
+        sum *= Weight::from(i);
+        Self(sum)
+    }
+}
+impl From<f64> for Coefficient
+{
+    fn from(x: f64) -> Self
+    {
+        let mut sum = PartialZero::zero();
+        let product: PoweredProduct<Sym, Int> = PartialOne::one();
+        sum += product;
+        sum *= Weight::from(x);
         Self(sum)
     }
+}
+
+impl<F> Compute<F> for Coefficient
+where
+    F: Float
+{
+    type Output = F;
+
+    fn compute(&self, syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        self.0.compute(syms)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,247 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Context>> = const {RefCell::new(None)};
+}
+
+/// The progress of a computation run in a [Context](Context).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress
+{
+    /// The number of term products formed so far.
+    pub terms: usize,
+    /// The degree of the polynomial most recently worked on.
+    pub degree: usize,
+    pub elapsed: Duration
+}
+
+/// Why a computation run in a [Context](Context) was stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt
+{
+    Cancelled,
+    /// More term products than the budget were formed.
+    BudgetExceeded {budget: usize}
+}
+
+impl Display for Interrupt
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            Interrupt::Cancelled => write!(f, "computation cancelled"),
+            Interrupt::BudgetExceeded {budget} => write!(f, "computation exceeded the budget of {} terms", budget)
+        }
+    }
+}
+
+impl std::error::Error for Interrupt {}
+
+type ProgressFn = Arc<dyn Fn(Progress) + Send + Sync>;
+
+struct Inner
+{
+    cancelled: AtomicBool,
+    terms: AtomicUsize,
+    degree: AtomicUsize,
+    budget: Option<usize>,
+    progress: Option<(Duration, ProgressFn)>,
+    /// Why the computation stopped, set at the first report after cancelling or exceeding the budget.
+    stopped: OnceLock<Interrupt>,
+    start: Instant,
+    last_report: Mutex<Instant>
+}
+
+/// A context for long-running computations, with progress reporting, cancellation and a budget on the number of terms.
+///
+/// Coefficient products report the number of term products they form to the context they are [run](Context::run) in,
+/// and polynomial products, [simplify](crate::simplify::Simplify::simplify) and
+/// [bilinear_transform](crate::Tf::bilinear_transform) report the degree they work on.
+/// Once stopped, coefficient products return zero without forming any terms, so the computation winds down quickly
+/// and its result is discarded.
+/// Clones share the same state, so a clone can cancel the computation from another thread.
+#[derive(Clone)]
+pub struct Context(Arc<Inner>);
+
+impl Default for Context
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Context
+{
+    pub fn new() -> Self
+    {
+        Self::build(None, None)
+    }
+
+    fn build(budget: Option<usize>, progress: Option<(Duration, ProgressFn)>) -> Self
+    {
+        let start = Instant::now();
+        Context(Arc::new(Inner {
+            cancelled: AtomicBool::new(false),
+            terms: AtomicUsize::new(0),
+            degree: AtomicUsize::new(0),
+            budget,
+            progress,
+            stopped: OnceLock::new(),
+            start,
+            last_report: Mutex::new(start)
+        }))
+    }
+
+    /// Stops the computation once more than `budget` term products have been formed.
+    pub fn with_budget(self, budget: usize) -> Self
+    {
+        Self::build(Some(budget), self.0.progress.clone())
+    }
+
+    /// Calls `f` with the progress at most once every `interval`.
+    pub fn on_progress<F>(self, interval: Duration, f: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static
+    {
+        Self::build(self.0.budget, Some((interval, Arc::new(f))))
+    }
+
+    /// Stops the computation at the next report.
+    pub fn cancel(&self)
+    {
+        self.0.cancelled.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool
+    {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn progress(&self) -> Progress
+    {
+        Progress {
+            terms: self.0.terms.load(Ordering::Relaxed),
+            degree: self.0.degree.load(Ordering::Relaxed),
+            elapsed: self.0.start.elapsed()
+        }
+    }
+
+    /// Runs `f` in this context, returning an error if it was cancelled or exceeded the budget.
+    pub fn run<R, F>(&self, f: F) -> Result<R, Interrupt>
+    where
+        F: FnOnce() -> R
+    {
+        struct Restore(Option<Context>);
+        impl Drop for Restore
+        {
+            fn drop(&mut self)
+            {
+                enter(self.0.take());
+            }
+        }
+        let restore = Restore(enter(Some(self.clone())));
+        let y = f();
+        drop(restore);
+        match self.0.stopped.get()
+        {
+            Some(&interrupt) => Err(interrupt),
+            None => Ok(y)
+        }
+    }
+
+    /// Whether the computation should go on.
+    fn report(&self, terms: usize, degree: Option<usize>) -> bool
+    {
+        let inner = &self.0;
+        if inner.stopped.get().is_some()
+        {
+            return false
+        }
+        let total = inner.terms.fetch_add(terms, Ordering::Relaxed) + terms;
+        if let Some(degree) = degree
+        {
+            inner.degree.store(degree, Ordering::Relaxed);
+        }
+
+        if let Some(budget) = inner.budget
+        {
+            if total > budget
+            {
+                return self.stop(Interrupt::BudgetExceeded {budget})
+            }
+        }
+        if self.is_cancelled()
+        {
+            return self.stop(Interrupt::Cancelled)
+        }
+
+        if let Some((interval, f)) = &inner.progress
+        {
+            let now = Instant::now();
+            let due = {
+                let mut last = inner.last_report.lock().unwrap();
+                let due = now.duration_since(*last) >= *interval;
+                if due
+                {
+                    *last = now;
+                }
+                due
+            };
+            if due
+            {
+                f(self.progress())
+            }
+        }
+        true
+    }
+
+    fn stop(&self, interrupt: Interrupt) -> bool
+    {
+        let _ = self.0.stopped.set(interrupt);
+        false
+    }
+}
+
+/// Makes `context` current on this thread, returning the previous one.
+pub(crate) fn enter(context: Option<Context>) -> Option<Context>
+{
+    CURRENT.replace(context)
+}
+
+pub(crate) fn current() -> Option<Context>
+{
+    CURRENT.with_borrow(|context| context.clone())
+}
+
+/// Why the current context was stopped, if it was.
+pub(crate) fn stopped() -> Option<Interrupt>
+{
+    CURRENT.with_borrow(|context| context.as_ref().and_then(|context| context.0.stopped.get().copied()))
+}
+
+/// Reports the number of term products formed to the current context, if any.
+///
+/// Returns `false` if the computation was stopped, and the products should not be formed.
+pub(crate) fn report_terms(terms: usize) -> bool
+{
+    CURRENT.with_borrow(|context| match context
+    {
+        Some(context) => context.report(terms, None),
+        None => true
+    })
+}
+
+/// Reports the degree of the polynomial being worked on to the current context, if any.
+pub(crate) fn report_degree(degree: usize)
+{
+    CURRENT.with_borrow(|context| if let Some(context) = context
+    {
+        context.report(0, Some(degree));
+    })
+}
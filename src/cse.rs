@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{coefficient::Coefficient, parse::intern, partial_one::PartialOne, polynomial::Polynomial, powered_product::PoweredProduct, sym::Sym, weight::Weight, weighted_sum::WeightedSum, Int, Tf, TfVar};
+
+/// Temporaries and the rewritten expressions from common subexpression elimination.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cse<T>
+{
+    /// Assignments to the temporaries, in an order where each only depends on the ones before it.
+    pub assignments: Vec<(Sym, Coefficient)>,
+    pub exprs: Vec<T>
+}
+
+impl Coefficient
+{
+    /// Extracts subexpressions shared between the coefficients into temporaries named `prefix0`, `prefix1`, ...
+    ///
+    /// Sums repeated up to a monomial factor and sign are extracted first,
+    /// then products of two factors are extracted greedily, most frequent first.
+    /// The names of the temporaries are [interned](crate::parse::intern), since symbols are `&'static str`.
+    pub fn cse<'a, I>(coeffs: I, prefix: &str) -> Cse<Coefficient>
+    where
+        I: IntoIterator<Item = &'a Coefficient>
+    {
+        let mut exprs: Vec<Coefficient> = coeffs.into_iter()
+            .map(Coefficient::trim)
+            .collect();
+
+        let mut used: BTreeSet<&'static str> = exprs.iter()
+            .flat_map(|c| c.0.0.keys().flat_map(|e| e.0.keys().map(|s| s.0)).collect::<Vec<_>>())
+            .collect();
+        let mut i = 0;
+        let mut fresh = || loop
+        {
+            let name = format!("{}{}", prefix, i);
+            i += 1;
+            if !used.contains(name.as_str())
+            {
+                let name = intern(&name);
+                used.insert(name);
+                return Sym(name)
+            }
+        };
+
+        // Sums
+        let mut sums: Vec<(Coefficient, usize)> = vec![];
+        let split: Vec<_> = exprs.iter()
+            .map(|c| {
+                if c.0.0.len() < 2
+                {
+                    return None
+                }
+                let common = trim_product(Coefficient::common_coeffs(c.0.0.keys()));
+                let mut sum = Coefficient(WeightedSum(c.0.0.iter()
+                    .map(|(e, w)| (trim_product(e.clone()/common.clone()), *w))
+                    .collect()));
+                let neg = sum.0.0.values().next().is_some_and(|w| w.is_negative());
+                if neg
+                {
+                    sum = -sum;
+                }
+                match sums.iter_mut().find(|(s, _)| *s == sum)
+                {
+                    Some((_, n)) => *n += 1,
+                    None => sums.push((sum.clone(), 1))
+                }
+                Some((common, neg, sum))
+            }).collect();
+        let mut sum_assignments = vec![];
+        let sum_temps: Vec<_> = sums.into_iter()
+            .map(|(sum, n)| if n >= 2
+            {
+                let t = fresh();
+                sum_assignments.push((t, sum.clone()));
+                Some((sum, t))
+            }
+            else
+            {
+                None
+            }).collect();
+        for (c, split) in exprs.iter_mut().zip(split)
+        {
+            let Some((mut common, neg, sum)) = split
+            else
+            {
+                continue
+            };
+            if let Some(&(_, t)) = sum_temps.iter().flatten().find(|(s, _)| *s == sum)
+            {
+                common *= t;
+                let mut terms = BTreeMap::new();
+                terms.insert(common, Weight::from(if neg {-1} else {1}));
+                *c = Coefficient(WeightedSum(terms));
+            }
+        }
+        exprs.extend(sum_assignments.iter().map(|(_, sum)| sum.clone()));
+
+        // Products
+        let mut assignments = vec![];
+        loop
+        {
+            let mut counts = BTreeMap::<(Sym, Sym, bool), usize>::new();
+            for e in exprs.iter().flat_map(|c| c.0.0.keys())
+            {
+                for pair in pairs(e)
+                {
+                    *counts.entry(pair).or_insert(0) += 1;
+                }
+            }
+            let best = counts.into_iter()
+                .fold(None, |best: Option<((Sym, Sym, bool), usize)>, (pair, n)| if best.is_none_or(|(_, m)| n > m) {Some((pair, n))} else {best});
+            let Some(((x, y, neg), 2..)) = best
+            else
+            {
+                break
+            };
+
+            let t = fresh();
+            let mut product = PoweredProduct(BTreeMap::new());
+            product *= x;
+            product *= y;
+            let mut terms = BTreeMap::new();
+            terms.insert(product, Weight::from(1));
+            assignments.push((t, Coefficient(WeightedSum(terms))));
+
+            let s = if neg {-1} else {1};
+            for c in exprs.iter_mut()
+            {
+                c.0.0 = std::mem::take(&mut c.0.0).into_iter()
+                    .map(|(mut e, w)| {
+                        if pairs(&e).contains(&(x, y, neg))
+                        {
+                            for sym in [x, y]
+                            {
+                                let p = e.0.get_mut(&sym).unwrap();
+                                *p -= s;
+                                if *p == 0
+                                {
+                                    e.0.remove(&sym);
+                                }
+                            }
+                            e.0.insert(t, s);
+                        }
+                        (e, w)
+                    }).collect();
+            }
+        }
+
+        let sum_assignments: Vec<_> = sum_assignments.into_iter()
+            .zip(exprs.split_off(exprs.len() - sum_temps.iter().flatten().count()))
+            .map(|((t, _), sum)| (t, sum))
+            .collect();
+        let assignments = dependency_order(sum_assignments.into_iter().chain(assignments).collect());
+
+        Cse {
+            assignments,
+            exprs
+        }
+    }
+}
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    /// Extracts subexpressions shared between all coefficients of the transfer functions into temporaries.
+    ///
+    /// See [Coefficient::cse](Coefficient::cse).
+    pub fn cse(tfs: &[Self], prefix: &str) -> Cse<Self>
+    {
+        let Cse {assignments, exprs} = Coefficient::cse(tfs.iter()
+            .flat_map(|h| h.0.0.iter().chain(h.1.0.iter())), prefix);
+        let mut exprs = exprs.into_iter();
+        let exprs = tfs.iter()
+            .map(|h| Tf(
+                Polynomial(exprs.by_ref().take(h.0.0.len()).collect()),
+                Polynomial(exprs.by_ref().take(h.1.0.len()).collect())
+            )).collect();
+
+        Cse {
+            assignments,
+            exprs
+        }
+    }
+}
+
+/// Orders the assignments so that each only depends on the ones before it, otherwise keeping them in order.
+///
+/// Products of two factors may contain temporaries for sums, and sums may contain temporaries for products.
+fn dependency_order(mut rest: Vec<(Sym, Coefficient)>) -> Vec<(Sym, Coefficient)>
+{
+    let uses = |c: &Coefficient, t: Sym| c.0.0.keys().any(|e| e.0.contains_key(&t));
+    let mut ordered = vec![];
+    while !rest.is_empty()
+    {
+        let i = rest.iter()
+            .position(|(_, c)| rest.iter().all(|&(t, _)| !uses(c, t)))
+            .expect("temporaries depend on each other");
+        ordered.push(rest.remove(i));
+    }
+    ordered
+}
+
+fn trim_product(mut e: PoweredProduct<Sym, Int>) -> PoweredProduct<Sym, Int>
+{
+    e.0.retain(|s, p| *p != 0 && !PartialOne::is_one(s));
+    e
+}
+
+/// Products of two factors in a term, with whether they are in the denominator.
+fn pairs(e: &PoweredProduct<Sym, Int>) -> Vec<(Sym, Sym, bool)>
+{
+    let mut pairs = vec![];
+    for (i, (&x, &p)) in e.0.iter().enumerate()
+    {
+        if p.abs() >= 2
+        {
+            pairs.push((x, x, p < 0));
+        }
+        for (&y, &q) in e.0.iter().skip(i + 1)
+        {
+            if p != 0 && q != 0 && (p < 0) == (q < 0)
+            {
+                pairs.push((x, y, p < 0));
+            }
+        }
+    }
+    pairs
+}
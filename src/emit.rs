@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+use crate::{Tf, TfVar};
+
+/// A format to print the coefficients of a transfer function in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format
+{
+    /// `b = [...]` and `a = [...]` on two lines.
+    #[default]
+    Text,
+    /// Two `let` bindings of arrays, with powers as `x.powi(n)`.
+    Rust,
+    /// An object with the arrays `b` and `a` of strings.
+    Json
+}
+
+impl FromStr for Format
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s
+        {
+            "text" => Ok(Format::Text),
+            "rust" => Ok(Format::Rust),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format `{}`", s))
+        }
+    }
+}
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    /// Prints the numerator coefficients `b` and the denominator coefficients `a` in the given format.
+    pub fn emit(&self, format: Format) -> String
+    {
+        let b: Vec<String> = self.0.0.iter().map(|b| b.to_string()).collect();
+        let a: Vec<String> = self.1.0.iter().map(|a| a.to_string()).collect();
+        match format
+        {
+            Format::Text => format!("b = [{}]\na = [{}]", b.join(", "), a.join(", ")),
+            Format::Rust => {
+                let array = |name: &str, c: &[String]| format!("let {} = [\n{}];", name, c.iter()
+                    .map(|c| format!("    {},\n", powi(c)))
+                    .collect::<String>());
+                format!("{}\n{}", array("b", &b), array("a", &a))
+            },
+            Format::Json => {
+                let array = |c: &[String]| c.iter()
+                    .map(|c| format!("\"{}\"", c.replace('\\', "\\\\").replace('"', "\\\"")))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{{\"b\": [{}], \"a\": [{}]}}", array(&b), array(&a))
+            }
+        }
+    }
+}
+
+/// Rewrites the powers `x^n` of a formatted coefficient as `x.powi(n)`, since `^` is exclusive or in Rust.
+fn powi(c: &str) -> String
+{
+    let mut y = String::new();
+    let mut rest = c;
+    while let Some(i) = rest.find('^')
+    {
+        let exp = &rest[i + 1..];
+        let n = exp.find(|c: char| !c.is_ascii_digit()).unwrap_or(exp.len());
+        y += &format!("{}.powi({})", &rest[..i], &exp[..n]);
+        rest = &exp[n..];
+    }
+    y + rest
+}
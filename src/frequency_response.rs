@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+use num::{Complex, Float};
+
+use crate::{compute::Compute, polynomial::Polynomial, Tf, TfVar};
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    /// Evaluates the frequency response numerically.
+    ///
+    /// In the s-domain this is `H(j*omega)` with `omega` in radians per second,
+    /// in the z-domain `H(e^(j*omega))` with `omega` in radians per sample.
+    pub fn frequency_response<F>(&self, syms: HashMap<&'static str, F>, omega: F) -> Option<Complex<F>>
+    where
+        F: Float
+    {
+        let (b, a) = self.compute(syms)?;
+        Some(frequency_response::<F, VAR>(&b, &a, omega))
+    }
+}
+
+/// Evaluates the frequency response of numeric coefficients in the domain `VAR`.
+pub(crate) fn frequency_response<F, const VAR: TfVar>(b: &Polynomial<F>, a: &Polynomial<F>, omega: F) -> Complex<F>
+where
+    F: Float
+{
+    let x = match VAR
+    {
+        TfVar::S => Complex::new(F::zero(), omega),
+        TfVar::Z => Complex::from_polar(F::one(), -omega)
+    };
+    b.horner(x)/a.horner(x)
+}
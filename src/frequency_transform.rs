@@ -0,0 +1,92 @@
+use std::f64::consts::TAU;
+
+use num::{One, Zero};
+
+use crate::{coefficient::Coefficient, parse::intern, polynomial::Polynomial, sym::Sym, Tf, TfVar};
+
+impl Tf<{TfVar::S}>
+{
+    /// Moves the cutoff of a prototype from one radian per second to `omega`, substituting `s -> s/omega`.
+    pub fn scale_frequency(&self, omega: Coefficient) -> Self
+    {
+        self.substitute_s(
+            &Polynomial(vec![Coefficient::from(0), Coefficient::from(1)]),
+            &Polynomial(vec![omega])
+        )
+    }
+
+    /// Prewarps for the [bilinear transform](Tf::bilinear_transform) at the sample rate `rate`,
+    /// so that the response matches at `f0` Hz instead of only at DC.
+    ///
+    /// Returns `None` unless `0 < f0 < rate/2`.
+    pub fn prewarp(&self, f0: f64, rate: f64) -> Option<Self>
+    {
+        if !(f0 > 0.0 && f0 < rate/2.0)
+        {
+            return None
+        }
+        // s -> s*k/(2*rate), with k = w0/tan(w0/(2*rate))
+        let w0 = TAU*f0;
+        let k = w0/(w0/(2.0*rate)).tan();
+        Some(self.scale_frequency((2.0*rate/k).into()))
+    }
+
+    /// Transforms a lowpass prototype into a highpass with the cutoff `omega`, substituting `s -> omega/s`.
+    pub fn to_highpass(&self, omega: Coefficient) -> Self
+    {
+        self.substitute_s(
+            &Polynomial(vec![omega]),
+            &Polynomial(vec![Coefficient::from(0), Coefficient::from(1)])
+        )
+    }
+
+    /// Transforms a lowpass prototype into a bandpass with the center `omega0` and bandwidth `bw`,
+    /// substituting `s -> (s^2 + omega0^2)/(bw*s)`.
+    ///
+    /// The order is doubled.
+    pub fn to_bandpass(&self, omega0: Coefficient, bw: Coefficient) -> Self
+    {
+        self.substitute_s(
+            &Polynomial(vec![omega0.clone()*omega0, Coefficient::from(0), Coefficient::from(1)]),
+            &Polynomial(vec![Coefficient::from(0), bw])
+        )
+    }
+
+    /// Transforms a lowpass prototype into a bandstop with the center `omega0` and bandwidth `bw`,
+    /// substituting `s -> bw*s/(s^2 + omega0^2)`.
+    ///
+    /// The order is doubled.
+    pub fn to_bandstop(&self, omega0: Coefficient, bw: Coefficient) -> Self
+    {
+        self.substitute_s(
+            &Polynomial(vec![Coefficient::from(0), bw]),
+            &Polynomial(vec![omega0.clone()*omega0, Coefficient::from(0), Coefficient::from(1)])
+        )
+    }
+
+    /// Substitutes `s -> num/den`, multiplying through by `den^order`.
+    ///
+    /// The polynomials are written as single coefficients in a placeholder symbol for `s`,
+    /// which is then [substituted](Tf::substitute).
+    fn substitute_s(&self, num: &Polynomial<Coefficient>, den: &Polynomial<Coefficient>) -> Self
+    {
+        let used = |name: &str| self.0.0.iter().chain(self.1.0.iter())
+            .any(|c| c.0.0.keys().any(|e| e.0.keys().any(|s| s.0 == name)));
+        let name = (0..).map(|k| if k == 0 {"s".to_string()} else {format!("s{}", k)})
+            .find(|name| !used(name))
+            .unwrap();
+        let x = Sym(intern(&name));
+
+        let in_x = |p: &Polynomial<Coefficient>| {
+            let mut y = Coefficient::zero();
+            let mut x_pow = Coefficient::one();
+            for c in p.0.iter()
+            {
+                y += c.clone()*x_pow.clone();
+                x_pow *= Coefficient::from(x);
+            }
+            Polynomial(vec![y])
+        };
+        Tf(in_x(&self.0), in_x(&self.1)).substitute(x, &Tf(num.clone(), den.clone()))
+    }
+}
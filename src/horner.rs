@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use num::{One, Zero};
+
+use crate::{coefficient::Coefficient, powered_product::PoweredProduct, sym::Sym, weight::Weight, Int};
+
+/// Displays a [Coefficient](Coefficient) in multivariate Horner form.
+///
+/// Common monomials are pulled out, and the terms are recursively collected by the most frequent symbol,
+/// which minimizes the number of multiplications when the output is used as code.
+pub struct Horner<'a>(pub &'a Coefficient);
+
+type Term = (PoweredProduct<Sym, Int>, Weight);
+
+impl Coefficient
+{
+    pub fn horner(&self) -> Horner<'_>
+    {
+        Horner(self)
+    }
+}
+
+impl Display for Horner<'_>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let terms: Vec<Term> = self.0.0.0.iter()
+            .filter(|(_, w)| !w.is_zero())
+            .map(|(e, w)| (trim(e.clone()), *w))
+            .collect();
+        write!(f, "{}", horner(terms).0)
+    }
+}
+
+fn trim(mut e: PoweredProduct<Sym, Int>) -> PoweredProduct<Sym, Int>
+{
+    e.0.retain(|_, p| *p != 0);
+    e
+}
+
+/// Returns the formatted terms, and whether they form a sum.
+fn horner(terms: Vec<Term>) -> (String, bool)
+{
+    match terms.len()
+    {
+        0 => return (format!("{:.1}", 0.0), false),
+        1 => return (monomial(&terms[0].0, terms[0].1), false),
+        _ => ()
+    }
+
+    let common = trim(Coefficient::common_coeffs(terms.iter().map(|(e, _)| e)));
+    if !common.0.is_empty()
+    {
+        let rest = terms.into_iter()
+            .map(|(e, w)| (trim(e/common.clone()), w))
+            .collect();
+        return (product(&monomial(&common, Weight::one()), horner(rest)), false)
+    }
+
+    let mut counts = BTreeMap::<Sym, usize>::new();
+    for (e, _) in terms.iter()
+    {
+        for (&sym, &p) in e.0.iter()
+        {
+            if p > 0
+            {
+                *counts.entry(sym).or_insert(0) += 1;
+            }
+        }
+    }
+    let x = counts.iter()
+        .fold(None, |x: Option<(Sym, usize)>, (&sym, &n)| if x.is_none_or(|(_, m)| n > m) {Some((sym, n))} else {x});
+    let Some((x, 2..)) = x
+    else
+    {
+        return (sum(terms.iter().map(|(e, w)| monomial(e, *w)).collect()), true)
+    };
+
+    let mut rest = vec![];
+    let mut powers = BTreeMap::<Int, Vec<Term>>::new();
+    for (mut e, w) in terms
+    {
+        match e.0.get(&x).copied()
+        {
+            Some(p) if p > 0 => {
+                e.0.remove(&x);
+                powers.entry(p).or_default().push((e, w));
+            },
+            _ => rest.push((e, w))
+        }
+    }
+
+    // x^p1*(c1 + x^(p2 - p1)*(c2 + ...))
+    let x_pow = |p: Int| {
+        let mut x_pow = PoweredProduct(BTreeMap::new());
+        x_pow.0.insert(x, p);
+        monomial(&x_pow, Weight::one())
+    };
+    let mut powers: Vec<(Int, Vec<Term>)> = powers.into_iter().collect();
+    let (mut p_outer, c) = powers.pop().unwrap();
+    let mut inner = horner(c);
+    while let Some((p, c)) = powers.pop()
+    {
+        inner = (sum(vec![horner(c).0, product(&x_pow(p_outer - p), inner)]), true);
+        p_outer = p;
+    }
+    let chain = (product(&x_pow(p_outer), inner), false);
+
+    if rest.is_empty()
+    {
+        return chain
+    }
+    (sum(vec![horner(rest).0, chain.0]), true)
+}
+
+fn product(factor: &str, (x, x_sum): (String, bool)) -> String
+{
+    if x == format!("{:.1}", 1.0)
+    {
+        factor.to_string()
+    }
+    else if x_sum
+    {
+        format!("{}*({})", factor, x)
+    }
+    else
+    {
+        format!("{}*{}", factor, x)
+    }
+}
+
+fn sum(terms: Vec<String>) -> String
+{
+    let mut y = String::new();
+    for (i, term) in terms.into_iter().enumerate()
+    {
+        if i == 0
+        {
+            y += &term;
+        }
+        else if let Some(term) = term.strip_prefix('-')
+        {
+            y += " - ";
+            y += term;
+        }
+        else
+        {
+            y += " + ";
+            y += &term;
+        }
+    }
+    y
+}
+
+fn monomial(e: &PoweredProduct<Sym, Int>, w: Weight) -> String
+{
+    let mut y = String::new();
+    if w.is_negative()
+    {
+        y += "-";
+    }
+    let w = w.abs();
+    let mut first = true;
+    if !w.is_one()
+    {
+        y += &format!("{}", w);
+        first = false;
+    }
+    for (e, &p) in e.0.iter()
+    {
+        if p == 0
+        {
+            continue
+        }
+        if first
+        {
+            if p < 0
+            {
+                y += &format!("{:.1}/", 1.0);
+            }
+            first = false;
+        }
+        else
+        {
+            y += if p < 0 {"/"} else {"*"};
+        }
+        y += &format!("{}", e);
+        if p.abs() != 1
+        {
+            y += &format!("^{}", p.abs());
+        }
+    }
+    if first
+    {
+        y += &format!("{:.1}", 1.0);
+    }
+    y
+}
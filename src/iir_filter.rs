@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use num::Float;
+
+use crate::{compute::Compute, polynomial::Polynomial, sos::{self, SosOrder}, Tf, TfVar};
+
+/// The realization structure of an [IirFilter](IirFilter).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IirStructure
+{
+    DirectFormI,
+    DirectFormIITransposed,
+    Sos(SosOrder)
+}
+
+/// A numeric IIR filter running the difference equation of a z-domain transfer function sample by sample.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IirFilter<F>
+where
+    F: Float
+{
+    DirectFormI
+    {
+        b: Vec<F>,
+        a: Vec<F>,
+        x: Vec<F>,
+        y: Vec<F>
+    },
+    DirectFormIITransposed
+    {
+        b: Vec<F>,
+        a: Vec<F>,
+        w: Vec<F>
+    },
+    Sos
+    {
+        order: SosOrder,
+        sections: Vec<[F; 6]>,
+        w: Vec<[F; 2]>
+    }
+}
+
+impl<F> IirFilter<F>
+where
+    F: Float
+{
+    /// Evaluates the transfer function numerically and realizes it with the given structure.
+    ///
+    /// Returns `None` if a symbol is missing from `syms`, or if the filter is not causal.
+    pub fn new(tf: &Tf<{TfVar::Z}>, syms: HashMap<&'static str, F>, structure: IirStructure) -> Option<Self>
+    {
+        let (b, a) = tf.compute(syms)?;
+        Self::from_coeffs(&b, &a, structure)
+    }
+
+    /// Realizes a numeric transfer function in powers of `z^-1` with the given structure.
+    pub fn from_coeffs(b: &Polynomial<F>, a: &Polynomial<F>, structure: IirStructure) -> Option<Self>
+    {
+        let mut filter = match structure
+        {
+            IirStructure::DirectFormI => IirFilter::DirectFormI {b: vec![], a: vec![], x: vec![], y: vec![]},
+            IirStructure::DirectFormIITransposed => IirFilter::DirectFormIITransposed {b: vec![], a: vec![], w: vec![]},
+            IirStructure::Sos(order) => IirFilter::Sos {order, sections: vec![], w: vec![]}
+        };
+        filter.set_coeffs(b, a)?;
+        Some(filter)
+    }
+
+    pub fn structure(&self) -> IirStructure
+    {
+        match self
+        {
+            IirFilter::DirectFormI {..} => IirStructure::DirectFormI,
+            IirFilter::DirectFormIITransposed {..} => IirStructure::DirectFormIITransposed,
+            IirFilter::Sos {order, ..} => IirStructure::Sos(*order)
+        }
+    }
+
+    /// Re-evaluates the transfer function with new symbol values, keeping the filter state.
+    pub fn update(&mut self, tf: &Tf<{TfVar::Z}>, syms: HashMap<&'static str, F>) -> Option<()>
+    {
+        let (b, a) = tf.compute(syms)?;
+        self.set_coeffs(&b, &a)
+    }
+
+    /// Replaces the coefficients, keeping the filter state.
+    ///
+    /// If the order changes, state is truncated or padded with zeros.
+    /// On failure the filter is left unchanged.
+    pub fn set_coeffs(&mut self, b: &Polynomial<F>, a: &Polynomial<F>) -> Option<()>
+    {
+        match self
+        {
+            IirFilter::DirectFormI {b: fb, a: fa, x, y} => {
+                let (b, a) = normalize(b, a)?;
+                x.resize(b.len() - 1, F::zero());
+                y.resize(a.len() - 1, F::zero());
+                *fb = b;
+                *fa = a;
+            },
+            IirFilter::DirectFormIITransposed {b: fb, a: fa, w} => {
+                let (mut b, mut a) = normalize(b, a)?;
+                let n = b.len().max(a.len());
+                b.resize(n, F::zero());
+                a.resize(n, F::zero());
+                w.resize(n - 1, F::zero());
+                *fb = b;
+                *fa = a;
+            },
+            IirFilter::Sos {order, sections, w} => {
+                let s = sos::sos(b, a, *order)?;
+                w.resize(s.len(), [F::zero(); 2]);
+                *sections = s;
+            }
+        }
+        Some(())
+    }
+
+    /// Clears the filter state.
+    pub fn reset(&mut self)
+    {
+        match self
+        {
+            IirFilter::DirectFormI {x, y, ..} => {
+                x.fill(F::zero());
+                y.fill(F::zero());
+            },
+            IirFilter::DirectFormIITransposed {w, ..} => w.fill(F::zero()),
+            IirFilter::Sos {w, ..} => w.fill([F::zero(); 2])
+        }
+    }
+
+    pub fn process_sample(&mut self, x0: F) -> F
+    {
+        match self
+        {
+            IirFilter::DirectFormI {b, a, x, y} => {
+                let mut y0 = b[0]*x0;
+                for (b, x) in b[1..].iter().zip(x.iter())
+                {
+                    y0 = y0 + *b**x;
+                }
+                for (a, y) in a[1..].iter().zip(y.iter())
+                {
+                    y0 = y0 - *a**y;
+                }
+                if !x.is_empty()
+                {
+                    x.rotate_right(1);
+                    x[0] = x0;
+                }
+                if !y.is_empty()
+                {
+                    y.rotate_right(1);
+                    y[0] = y0;
+                }
+                y0
+            },
+            IirFilter::DirectFormIITransposed {b, a, w} => {
+                let n = w.len();
+                let y0 = b[0]*x0 + w.first().copied().unwrap_or(F::zero());
+                for i in 0..n
+                {
+                    let w_next = if i + 1 < n {w[i + 1]} else {F::zero()};
+                    w[i] = b[i + 1]*x0 - a[i + 1]*y0 + w_next;
+                }
+                y0
+            },
+            IirFilter::Sos {sections, w, ..} => {
+                let mut x0 = x0;
+                for (s, w) in sections.iter().zip(w.iter_mut())
+                {
+                    let y0 = s[0]*x0 + w[0];
+                    w[0] = s[1]*x0 - s[4]*y0 + w[1];
+                    w[1] = s[2]*x0 - s[5]*y0;
+                    x0 = y0;
+                }
+                x0
+            }
+        }
+    }
+
+    /// Filters a block of samples in place.
+    pub fn process_block(&mut self, block: &mut [F])
+    {
+        for x in block.iter_mut()
+        {
+            *x = self.process_sample(*x)
+        }
+    }
+}
+
+/// Trims trailing zeros and scales the coefficients so that `a0 = 1`.
+fn normalize<F>(b: &Polynomial<F>, a: &Polynomial<F>) -> Option<(Vec<F>, Vec<F>)>
+where
+    F: Float
+{
+    let mut b = b.0.clone();
+    let mut a = a.0.clone();
+    while b.len() > 1 && b.last().is_some_and(|b| b.is_zero())
+    {
+        b.pop();
+    }
+    while a.last().is_some_and(|a| a.is_zero())
+    {
+        a.pop();
+    }
+    let a0 = *a.first()?;
+    if a0.is_zero()
+    {
+        return None
+    }
+    if b.is_empty()
+    {
+        b.push(F::zero());
+    }
+    Some((
+        b.into_iter().map(|b| b/a0).collect(),
+        a.into_iter().map(|a| a/a0).collect()
+    ))
+}
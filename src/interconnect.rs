@@ -0,0 +1,53 @@
+use num::One;
+
+use crate::{simplify::Simplify, Tf, TfVar};
+
+/// The sign with which the feedback path is summed into the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeedbackSign
+{
+    /// `G/(1 + G*H)`
+    #[default]
+    Negative,
+    /// `G/(1 - G*H)`
+    Positive
+}
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    /// The transfer function of `self` followed by `h`.
+    pub fn series(&self, h: &Self) -> Self
+    {
+        self*h
+    }
+
+    /// The sum of the outputs of `self` and `h` driven by the same input.
+    pub fn parallel(&self, h: &Self) -> Self
+    {
+        self + h
+    }
+
+    /// Closes a loop with `self` in the forward path and `h` in the feedback path.
+    pub fn feedback(&self, h: &Self, sign: FeedbackSign) -> Self
+    {
+        // G/(1 -+ G*H) = bg*ah/(ag*ah -+ bg*bh)
+        let loop_gain = &self.0*&h.0;
+        let ag_ah = &self.1*&h.1;
+        let mut y = Tf(
+            &self.0*&h.1,
+            match sign
+            {
+                FeedbackSign::Negative => ag_ah + loop_gain,
+                FeedbackSign::Positive => ag_ah - loop_gain
+            }
+        );
+        y.simplify();
+        y
+    }
+
+    /// Closes a loop with `self` in the forward path and a unit gain in the feedback path.
+    pub fn unity_feedback(&self, sign: FeedbackSign) -> Self
+    {
+        self.feedback(&One::one(), sign)
+    }
+}
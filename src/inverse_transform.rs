@@ -0,0 +1,905 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+use num::{Complex, Float, One, Zero};
+
+use crate::{compute::Compute, coefficient::Coefficient, polynomial::Polynomial, powered_product::PoweredProduct, sos, sym::Sym, weight::Weight, weighted_sum::WeightedSum, Int, Tf, TfVar};
+
+/// A symbolic expression in the time domain, as produced by inverse Laplace and inverse Z transforms.
+///
+/// Numeric evaluation reads the time variable (`t` or `n`) from the symbol table like any other symbol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeExpr
+{
+    Var(&'static str),
+    Const(Coefficient),
+    Neg(Box<TimeExpr>),
+    Add(Vec<TimeExpr>),
+    Mul(Vec<TimeExpr>),
+    Div(Box<TimeExpr>, Box<TimeExpr>),
+    Pow(Box<TimeExpr>, Box<TimeExpr>),
+    Sqrt(Box<TimeExpr>),
+    Exp(Box<TimeExpr>),
+    Sin(Box<TimeExpr>),
+    Cos(Box<TimeExpr>),
+    Acos(Box<TimeExpr>),
+    /// The unit step, `1` for non-negative arguments.
+    Step(Box<TimeExpr>),
+    /// The discrete unit impulse, `1` for a zero argument.
+    Delta(Box<TimeExpr>)
+}
+
+impl TimeExpr
+{
+    fn is_zero(&self) -> bool
+    {
+        matches!(self, TimeExpr::Const(c) if Zero::is_zero(c))
+    }
+
+    fn is_one(&self) -> bool
+    {
+        matches!(self, TimeExpr::Const(c) if One::is_one(c))
+    }
+
+    fn sum(terms: Vec<TimeExpr>) -> TimeExpr
+    {
+        let mut terms: Vec<TimeExpr> = terms.into_iter()
+            .filter(|x| !x.is_zero())
+            .collect();
+        match terms.len()
+        {
+            0 => TimeExpr::Const(Zero::zero()),
+            1 => terms.pop().unwrap(),
+            _ => TimeExpr::Add(terms)
+        }
+    }
+
+    fn product(factors: Vec<TimeExpr>) -> TimeExpr
+    {
+        if factors.iter().any(|x| x.is_zero())
+        {
+            return TimeExpr::Const(Zero::zero())
+        }
+        let mut factors: Vec<TimeExpr> = factors.into_iter()
+            .filter(|x| !x.is_one())
+            .collect();
+        match factors.len()
+        {
+            0 => TimeExpr::Const(One::one()),
+            1 => factors.pop().unwrap(),
+            _ => TimeExpr::Mul(factors)
+        }
+    }
+
+    fn div(num: TimeExpr, den: TimeExpr) -> TimeExpr
+    {
+        if den.is_one() || num.is_zero()
+        {
+            return num
+        }
+        TimeExpr::Div(Box::new(num), Box::new(den))
+    }
+
+    fn pow(base: TimeExpr, exp: TimeExpr) -> TimeExpr
+    {
+        if exp.is_zero()
+        {
+            return TimeExpr::Const(One::one())
+        }
+        if exp.is_one()
+        {
+            return base
+        }
+        TimeExpr::Pow(Box::new(base), Box::new(exp))
+    }
+
+    fn int(i: Int) -> TimeExpr
+    {
+        TimeExpr::Const(Coefficient::from(i))
+    }
+
+    fn level(&self) -> usize
+    {
+        match self
+        {
+            TimeExpr::Add(_) => 1,
+            TimeExpr::Neg(_) => 2,
+            TimeExpr::Mul(_) | TimeExpr::Div(..) => 3,
+            TimeExpr::Pow(..) => 4,
+            TimeExpr::Const(c) => match single_term(c)
+            {
+                None if Zero::is_zero(c) => 5,
+                None => 1,
+                Some((_, w)) if w.is_negative() => 2,
+                Some((e, w)) => {
+                    let powers: Vec<Int> = e.0.values()
+                        .copied()
+                        .filter(|&p| p != 0)
+                        .collect();
+                    let factors = powers.len() + if One::is_one(&w) {0} else {1};
+                    if factors > 1 || powers.iter().any(|&p| p < 0)
+                    {
+                        3
+                    }
+                    else if powers.first().is_some_and(|&p| p != 1)
+                    {
+                        4
+                    }
+                    else
+                    {
+                        5
+                    }
+                }
+            },
+            _ => 5
+        }
+    }
+
+    fn fmt_level(&self, f: &mut std::fmt::Formatter<'_>, level: usize) -> std::fmt::Result
+    {
+        if self.level() < level
+        {
+            write!(f, "({})", self)
+        }
+        else
+        {
+            write!(f, "{}", self)
+        }
+    }
+
+    fn compute_complex<F>(&self, syms: &HashMap<&'static str, F>) -> Option<Complex<F>>
+    where
+        F: Float
+    {
+        Some(match self
+        {
+            TimeExpr::Var(v) => Complex::from(*syms.get(v)?),
+            TimeExpr::Const(c) => Complex::from(c.compute(syms.clone())?),
+            TimeExpr::Neg(x) => -x.compute_complex(syms)?,
+            TimeExpr::Add(x) => {
+                let mut y = Complex::zero();
+                for x in x.iter()
+                {
+                    y = y + x.compute_complex(syms)?;
+                }
+                y
+            },
+            TimeExpr::Mul(x) => {
+                let mut y = Complex::one();
+                for x in x.iter()
+                {
+                    y = y*x.compute_complex(syms)?;
+                }
+                y
+            },
+            TimeExpr::Div(x, y) => x.compute_complex(syms)?/y.compute_complex(syms)?,
+            TimeExpr::Pow(x, p) => {
+                let x = x.compute_complex(syms)?;
+                let p = p.compute_complex(syms)?;
+                if p.im.is_zero() && p.re.round() == p.re
+                {
+                    x.powi(p.re.to_i32()?)
+                }
+                else
+                {
+                    x.powc(p)
+                }
+            },
+            TimeExpr::Sqrt(x) => x.compute_complex(syms)?.sqrt(),
+            TimeExpr::Exp(x) => x.compute_complex(syms)?.exp(),
+            TimeExpr::Sin(x) => x.compute_complex(syms)?.sin(),
+            TimeExpr::Cos(x) => x.compute_complex(syms)?.cos(),
+            TimeExpr::Acos(x) => x.compute_complex(syms)?.acos(),
+            TimeExpr::Step(x) => if x.compute_complex(syms)?.re >= F::zero() {Complex::one()} else {Complex::zero()},
+            TimeExpr::Delta(x) => if x.compute_complex(syms)?.is_zero() {Complex::one()} else {Complex::zero()}
+        })
+    }
+}
+
+impl<F> Compute<F> for TimeExpr
+where
+    F: Float
+{
+    type Output = F;
+
+    fn compute(&self, syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        Some(self.compute_complex(&syms)?.re)
+    }
+}
+
+impl Display for TimeExpr
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            TimeExpr::Var(v) => write!(f, "{}", v),
+            TimeExpr::Const(c) => write!(f, "{}", c),
+            TimeExpr::Neg(x) => {
+                write!(f, "-")?;
+                x.fmt_level(f, 3)
+            },
+            TimeExpr::Add(x) => {
+                for (i, x) in x.iter().enumerate()
+                {
+                    match x
+                    {
+                        TimeExpr::Neg(x) if i != 0 => {
+                            write!(f, " - ")?;
+                            x.fmt_level(f, 2)?;
+                        },
+                        TimeExpr::Const(c) if i != 0 && is_neg_term(c) => {
+                            write!(f, " - {}", -c.clone())?;
+                        },
+                        x => {
+                            if i != 0
+                            {
+                                write!(f, " + ")?;
+                            }
+                            x.fmt_level(f, 1)?;
+                        }
+                    }
+                }
+                Ok(())
+            },
+            TimeExpr::Mul(x) => {
+                for (i, x) in x.iter().enumerate()
+                {
+                    if i != 0
+                    {
+                        write!(f, "*")?;
+                    }
+                    x.fmt_level(f, 3)?;
+                }
+                Ok(())
+            },
+            TimeExpr::Div(x, y) => {
+                x.fmt_level(f, 3)?;
+                write!(f, "/")?;
+                y.fmt_level(f, 4)
+            },
+            TimeExpr::Pow(x, p) => {
+                x.fmt_level(f, 5)?;
+                write!(f, "^")?;
+                p.fmt_level(f, 5)
+            },
+            TimeExpr::Sqrt(x) => write!(f, "sqrt({})", x),
+            TimeExpr::Exp(x) => write!(f, "exp({})", x),
+            TimeExpr::Sin(x) => write!(f, "sin({})", x),
+            TimeExpr::Cos(x) => write!(f, "cos({})", x),
+            TimeExpr::Acos(x) => write!(f, "acos({})", x),
+            TimeExpr::Step(x) => write!(f, "u({})", x),
+            TimeExpr::Delta(x) => write!(f, "delta({})", x)
+        }
+    }
+}
+
+/// A symbolic rational constant, kept as a zeroth order transfer function so that it is simplified on arithmetic.
+type Ratio = Tf<{TfVar::S}>;
+
+/// The most candidate roots tried when factoring a denominator symbolically.
+const MAX_CANDIDATES: usize = 4096;
+/// The relative distance below which numeric roots count as repeated.
+const REPEATED_ROOT_TOL: f64 = 1e-6;
+
+fn ratio(c: &Coefficient) -> Ratio
+{
+    Tf(Polynomial::from(c.clone()), Polynomial(vec![One::one()]))
+}
+
+/// The monomial and weight of a coefficient with a single term.
+fn single_term(c: &Coefficient) -> Option<(&PoweredProduct<Sym, Int>, Weight)>
+{
+    if c.0.0.len() != 1
+    {
+        return None
+    }
+    c.0.0.iter().next().map(|(e, &w)| (e, w))
+}
+
+/// Whether a coefficient is a single term with a negative weight, which is printed with a leading minus.
+fn is_neg_term(c: &Coefficient) -> bool
+{
+    single_term(c).is_some_and(|(_, w)| w.is_negative())
+}
+
+fn constant(r: &Ratio) -> TimeExpr
+{
+    let num = r.0.0.first().cloned().unwrap_or(Zero::zero());
+    let den = r.1.0.first().cloned().unwrap_or(Zero::zero());
+    if is_neg_term(&num)
+    {
+        return TimeExpr::Neg(Box::new(TimeExpr::div(TimeExpr::Const(-num), TimeExpr::Const(den))))
+    }
+    TimeExpr::div(TimeExpr::Const(num), TimeExpr::Const(den))
+}
+
+fn trim(p: &Polynomial<Coefficient>) -> Vec<Coefficient>
+{
+    let mut p = p.0.clone();
+    while p.last().is_some_and(Zero::is_zero)
+    {
+        p.pop();
+    }
+    p
+}
+
+fn factorial(k: usize) -> Int
+{
+    (1..=k as Int).product()
+}
+
+/// Drops symbols with a power of zero and merges the terms that become equal, so that cancellation shows as zero.
+fn normalize(c: Coefficient) -> Coefficient
+{
+    let mut y = BTreeMap::new();
+    for (mut e, w) in c.0.0
+    {
+        e.0.retain(|_, p| *p != 0);
+        y.entry(e)
+            .and_modify(|m| *m += w)
+            .or_insert(w);
+    }
+    y.retain(|_, w| !Zero::is_zero(w));
+    Coefficient(WeightedSum(y))
+}
+
+/// Factors a polynomial into first- and second-order factors, the first one carrying the leading coefficient.
+///
+/// Roots are first searched for exactly, among the ratios of divisors of the constant and leading terms.
+/// What remains is factored numerically if it has no symbols.
+/// Returns `None` if a factor above second order remains, or if numeric roots are repeated.
+fn factor(a: &[Coefficient]) -> Option<Vec<Vec<Coefficient>>>
+{
+    let mut a = a.to_vec();
+    let mut roots: Vec<Coefficient> = vec![];
+    while a.len() > 3
+    {
+        match exact_root(&a)
+        {
+            Some(c) => {
+                // A repeated root is divided out as often as it divides, so that what remains doesn't share it
+                loop
+                {
+                    // Synthetic division by s - c
+                    let n = a.len() - 1;
+                    let mut q = vec![Zero::zero(); n];
+                    q[n - 1] = a[n].clone();
+                    for i in (1..n).rev()
+                    {
+                        q[i - 1] = normalize(a[i].clone() + &c*&q[i]);
+                    }
+                    a = q;
+                    roots.push(c.clone());
+                    if a.len() <= 2 || !Zero::is_zero(&eval(&a, &c))
+                    {
+                        break
+                    }
+                }
+            },
+            None => break
+        }
+    }
+
+    let mut factors = vec![];
+    if a.len() > 3
+    {
+        factors = numeric_factors(&a)?;
+    }
+    else
+    {
+        factors.push(a);
+    }
+
+    // A double root makes one second-order factor, as factors must not share roots
+    while let Some(c) = roots.pop()
+    {
+        match roots.iter().position(|r| *r == c)
+        {
+            Some(i) => {
+                roots.remove(i);
+                if roots.contains(&c)
+                {
+                    return None
+                }
+                factors.push(vec![normalize(&c*&c), Coefficient::from(-2)*c, One::one()]);
+            },
+            None => factors.push(vec![-c, One::one()])
+        }
+    }
+    Some(factors)
+}
+
+/// Searches for an exact root of a polynomial with a single term as its constant and leading coefficients.
+fn exact_root(a: &[Coefficient]) -> Option<Coefficient>
+{
+    let (e0, w0) = single_term(a.first()?)?;
+    let (en, wn) = single_term(a.last()?)?;
+
+    let monomials = |e: &PoweredProduct<Sym, Int>| e.0.iter()
+        .fold(vec![PoweredProduct(Default::default())], |divisors: Vec<PoweredProduct<Sym, Int>>, (&sym, &p)| divisors.iter()
+            .flat_map(|d| (p.min(0)..=p.max(0)).map(move |q| {
+                let mut d = d.clone();
+                if q != 0
+                {
+                    d.0.insert(sym, q);
+                }
+                d
+            })).collect()
+        );
+    let weights = |w: Weight| match w
+    {
+        Weight::Int(w) if w.unsigned_abs() <= 1 << 20 => (1..=w.abs()).filter(|d| w % d == 0).map(Weight::Int).collect(),
+        w => vec![One::one(), w.abs()]
+    };
+    let (m0, mn, w0, wn) = (monomials(e0), monomials(en), weights(w0), weights(wn));
+    if m0.len()*mn.len()*w0.len()*wn.len() > MAX_CANDIDATES
+    {
+        return None
+    }
+
+    for d0 in m0.iter()
+    {
+        for dn in mn.iter()
+        {
+            for &v0 in w0.iter()
+            {
+                for &vn in wn.iter()
+                {
+                    let c = normalize(Coefficient(WeightedSum([(d0.clone()/dn.clone(), v0/vn)].into_iter().collect())));
+                    for c in [-c.clone(), c]
+                    {
+                        if Zero::is_zero(&eval(a, &c))
+                        {
+                            return Some(c)
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Evaluates a polynomial at `c` with Horner's method, normalized so that an exact root gives zero.
+fn eval(a: &[Coefficient], c: &Coefficient) -> Coefficient
+{
+    let mut y = a.last().unwrap().clone();
+    for a in a.iter().rev().skip(1)
+    {
+        y = normalize(&y*c + a.clone());
+    }
+    y
+}
+
+/// Factors a polynomial without symbols at its numeric roots, paired into real first- and second-order factors.
+fn numeric_factors(a: &[Coefficient]) -> Option<Vec<Vec<Coefficient>>>
+{
+    let a: Vec<f64> = a.iter()
+        .map(|a| a.compute(HashMap::new()))
+        .collect::<Option<_>>()?;
+    let roots = sos::conjugate_pairs(Polynomial(a.clone()).roots());
+    for (i, r1) in roots.iter().enumerate()
+    {
+        if roots[i + 1..].iter().any(|r2| (r1 - r2).norm() <= REPEATED_ROOT_TOL*r1.norm().max(1.0))
+        {
+            return None
+        }
+    }
+
+    let mut factors = vec![];
+    let mut roots = roots.into_iter();
+    while let Some(r) = roots.next()
+    {
+        factors.push(if r.im == 0.0
+        {
+            vec![-r.re, 1.0]
+        }
+        else
+        {
+            roots.next();
+            vec![r.norm_sqr(), -2.0*r.re, 1.0]
+        });
+    }
+    let lead = *a.last().unwrap();
+    Some(factors.into_iter()
+        .enumerate()
+        .map(|(i, f)| f.into_iter()
+            .map(|f| Coefficient::from(if i == 0 {f*lead} else {f}))
+            .collect()
+        ).collect())
+}
+
+fn mul_ratios(a: &[Ratio], b: &[Ratio]) -> Vec<Ratio>
+{
+    let mut y = vec![Zero::zero(); (a.len() + b.len()).saturating_sub(1)];
+    for (i, a) in a.iter().enumerate()
+    {
+        for (j, b) in b.iter().enumerate()
+        {
+            y[i + j] = &y[i + j] + &(a*b);
+        }
+    }
+    y
+}
+
+/// The remainder of `a` divided by `g`, with as many coefficients as the order of `g`.
+fn rem_ratios(a: &[Ratio], g: &[Ratio]) -> Vec<Ratio>
+{
+    let d = g.len() - 1;
+    let mut r = a.to_vec();
+    for i in (d..r.len()).rev()
+    {
+        let q = &r[i]/&g[d];
+        for j in 0..=d
+        {
+            r[i - d + j] = &r[i - d + j] - &(&q*&g[j]);
+        }
+    }
+    r.resize(d, Zero::zero());
+    r
+}
+
+/// The inverse of `m` modulo `g`, or `None` if they share a root.
+fn inv_mod(m: &[Ratio], g: &[Ratio]) -> Option<Vec<Ratio>>
+{
+    // Solves m*r = 1 mod g, with the columns m*s^j mod g
+    let d = g.len() - 1;
+    let cols: Vec<Vec<Ratio>> = (0..d).map(|j| {
+            let mut x = vec![Zero::zero(); j];
+            x.extend(m.iter().cloned());
+            rem_ratios(&x, g)
+        }).collect();
+    let mut rows: Vec<Vec<Ratio>> = (0..d).map(|i| cols.iter()
+            .map(|c| c[i].clone())
+            .chain([if i == 0 {One::one()} else {Zero::zero()}])
+            .collect()
+        ).collect();
+    for j in 0..d
+    {
+        let p = (j..d).find(|&i| !Zero::is_zero(&rows[i][j]))?;
+        rows.swap(j, p);
+        for i in 0..d
+        {
+            if i != j && !Zero::is_zero(&rows[i][j])
+            {
+                let f = &rows[i][j]/&rows[j][j];
+                let pivot = rows[j].clone();
+                for (x, p) in rows[i][j..].iter_mut().zip(pivot[j..].iter())
+                {
+                    *x = &*x - &(&f*p);
+                }
+            }
+        }
+    }
+    Some(rows.iter()
+        .enumerate()
+        .map(|(j, row)| &row[d]/&row[j])
+        .collect())
+}
+
+/// Splits `b` over the product of the factors into a proper fraction over each factor.
+///
+/// The factors must not share roots, and `b` must be of lower order than their product.
+fn partial_fractions(b: &[Ratio], factors: &[Vec<Ratio>]) -> Option<Vec<Vec<Ratio>>>
+{
+    factors.iter()
+        .enumerate()
+        .map(|(i, g)| {
+            let m = factors.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(vec![One::one()], |m, (_, f)| rem_ratios(&mul_ratios(&m, f), g));
+            let r = inv_mod(&m, g)?;
+            Some(rem_ratios(&mul_ratios(&rem_ratios(b, g), &r), g))
+        }).collect()
+}
+
+/// The terms of `B/(s^k*A)`, where `A` is of at most second order and `B` has `k + n` coefficients.
+fn laplace_terms(b: &[Ratio], k: usize, a: &[Coefficient]) -> Vec<TimeExpr>
+{
+    let t = || TimeExpr::Var("t");
+    let n = a.len() - 1;
+    let ar: Vec<Ratio> = a.iter()
+        .map(ratio)
+        .collect();
+
+    // B/(s^k*A) = C/s^k + D/A
+    let mut c: Vec<Ratio> = vec![];
+    for j in 0..k
+    {
+        let mut x = b[j].clone();
+        for i in 1..=j.min(n)
+        {
+            x = x - &(&ar[i]*&c[j - i]);
+        }
+        c.push(&x/&ar[0]);
+    }
+    let d: Vec<Ratio> = (0..n).map(|i| {
+            let mut x = b[k + i].clone();
+            for (j, c) in c.iter().enumerate()
+            {
+                if let Some(a) = ar.get(k + i - j)
+                {
+                    x = x - &(c*a);
+                }
+            }
+            x
+        }).collect();
+
+    let mut terms = vec![];
+    for (j, c) in c.iter().enumerate()
+    {
+        let p = k - 1 - j;
+        terms.push(TimeExpr::product(vec![
+            constant(&(c.clone()/Tf::from(factorial(p)))),
+            TimeExpr::pow(t(), TimeExpr::int(p as Int))
+        ]));
+    }
+    match n
+    {
+        1 => {
+            let sigma = &ar[0]/&ar[1];
+            terms.push(TimeExpr::product(vec![
+                constant(&(&d[0]/&ar[1])),
+                TimeExpr::Exp(Box::new(TimeExpr::product(vec![constant(&-sigma), t()])))
+            ]));
+        },
+        2 => {
+            let disc = a[1].clone()*a[1].clone() - Coefficient::from(4)*a[0].clone()*a[2].clone();
+            let sigma = ar[1].clone()/(ar[2].clone()*2);
+            let decay = TimeExpr::Exp(Box::new(TimeExpr::product(vec![constant(&-sigma.clone()), t()])));
+            if Zero::is_zero(&disc)
+            {
+                terms.push(TimeExpr::product(vec![
+                    TimeExpr::sum(vec![
+                        constant(&(&d[1]/&ar[2])),
+                        TimeExpr::product(vec![
+                            constant(&(&(d[0].clone() - &(&d[1]*&sigma))/&ar[2])),
+                            t()
+                        ])
+                    ]),
+                    decay
+                ]));
+            }
+            else
+            {
+                let omega = TimeExpr::div(
+                    TimeExpr::Sqrt(Box::new(TimeExpr::Const(-disc))),
+                    constant(&(ar[2].clone()*2))
+                );
+                let omega_t = TimeExpr::product(vec![omega.clone(), t()]);
+                terms.push(TimeExpr::product(vec![
+                    TimeExpr::sum(vec![
+                        TimeExpr::product(vec![constant(&(&d[1]/&ar[2])), TimeExpr::Cos(Box::new(omega_t.clone()))]),
+                        TimeExpr::product(vec![
+                            TimeExpr::div(constant(&(&(d[0].clone() - &(&d[1]*&sigma))/&ar[2])), omega),
+                            TimeExpr::Sin(Box::new(omega_t))
+                        ])
+                    ]),
+                    decay
+                ]));
+            }
+        },
+        _ => ()
+    }
+    terms
+}
+
+/// The causal terms of `R/A` in powers of `z^-1`, where `A` is of at most second order and `R` has `n` coefficients.
+fn z_terms(r: &[Ratio], a: &[Coefficient]) -> Vec<TimeExpr>
+{
+    let n_var = || TimeExpr::Var("n");
+    let ar: Vec<Ratio> = a.iter()
+        .map(ratio)
+        .collect();
+
+    let mut causal = vec![];
+    match a.len() - 1
+    {
+        1 => {
+            let p = -(&ar[1]/&ar[0]);
+            causal.push(TimeExpr::product(vec![
+                constant(&(&r[0]/&ar[0])),
+                TimeExpr::pow(constant(&p), n_var())
+            ]));
+        },
+        2 => {
+            let disc = a[1].clone()*a[1].clone() - Coefficient::from(4)*a[0].clone()*a[2].clone();
+            if Zero::is_zero(&disc)
+            {
+                let p = -(ar[1].clone()/(ar[0].clone()*2));
+                causal.push(TimeExpr::product(vec![
+                    constant(&(&r[0]/&ar[0])),
+                    TimeExpr::sum(vec![n_var(), TimeExpr::int(1)]),
+                    TimeExpr::pow(constant(&p), n_var())
+                ]));
+                causal.push(TimeExpr::product(vec![
+                    constant(&(&r[1]/&ar[0])),
+                    n_var(),
+                    TimeExpr::pow(constant(&p), TimeExpr::sum(vec![n_var(), TimeExpr::int(-1)]))
+                ]));
+            }
+            else
+            {
+                let rho = TimeExpr::Sqrt(Box::new(constant(&(&ar[2]/&ar[0]))));
+                let theta = TimeExpr::Acos(Box::new(TimeExpr::div(
+                    constant(&-ar[1].clone()),
+                    TimeExpr::product(vec![constant(&(ar[0].clone()*2)), rho.clone()])
+                )));
+                let sin_theta = TimeExpr::Sin(Box::new(theta.clone()));
+                causal.push(TimeExpr::product(vec![
+                    constant(&(&r[0]/&ar[0])),
+                    TimeExpr::pow(rho.clone(), n_var()),
+                    TimeExpr::div(
+                        TimeExpr::Sin(Box::new(TimeExpr::product(vec![TimeExpr::sum(vec![n_var(), TimeExpr::int(1)]), theta.clone()]))),
+                        sin_theta.clone()
+                    )
+                ]));
+                causal.push(TimeExpr::product(vec![
+                    constant(&(&r[1]/&ar[0])),
+                    TimeExpr::pow(rho, TimeExpr::sum(vec![n_var(), TimeExpr::int(-1)])),
+                    TimeExpr::div(
+                        TimeExpr::Sin(Box::new(TimeExpr::product(vec![n_var(), theta]))),
+                        sin_theta
+                    )
+                ]));
+            }
+        },
+        _ => ()
+    }
+    causal
+}
+
+impl Tf<{TfVar::S}>
+{
+    /// Computes the inverse Laplace transform symbolically, as a function of `t`.
+    ///
+    /// The transfer function must be strictly proper, and its denominator must factor into `s^k` and first- and second-order terms,
+    /// either exactly or numerically if it has no symbols. Products of factors are split into partial fractions.
+    /// Second order terms with symbolically repeated roots give `t*exp(p*t)`-type terms, otherwise damped oscillations,
+    /// which are also valid when the roots turn out real on evaluation.
+    pub fn inverse_laplace(&self) -> Option<TimeExpr>
+    {
+        let b = trim(&self.0);
+        let a = trim(&self.1);
+        if a.is_empty()
+        {
+            return None
+        }
+        if b.is_empty()
+        {
+            return Some(TimeExpr::Const(Zero::zero()))
+        }
+        if b.len() >= a.len()
+        {
+            return None
+        }
+        let k = a.iter()
+            .take_while(|a| Zero::is_zero(*a))
+            .count();
+        let a = &a[k..];
+        let b: Vec<Ratio> = b.iter()
+            .map(ratio)
+            .chain(std::iter::repeat(Zero::zero()))
+            .take(k + a.len() - 1)
+            .collect();
+
+        let factors = factor(a)?;
+        let terms = if let [a] = &factors[..]
+        {
+            laplace_terms(&b, k, a)
+        }
+        else
+        {
+            let mut groups: Vec<Vec<Ratio>> = factors.iter()
+                .map(|f| f.iter().map(ratio).collect())
+                .collect();
+            if k > 0
+            {
+                let mut sk = vec![Zero::zero(); k];
+                sk.push(One::one());
+                groups.push(sk);
+            }
+            let parts = partial_fractions(&b, &groups)?;
+            let mut terms: Vec<TimeExpr> = factors.iter()
+                .zip(parts.iter())
+                .flat_map(|(f, b)| laplace_terms(b, 0, f))
+                .collect();
+            if k > 0
+            {
+                terms.extend(laplace_terms(parts.last().unwrap(), k, &[One::one()]));
+            }
+            terms
+        };
+
+        Some(TimeExpr::product(vec![
+            TimeExpr::sum(terms),
+            TimeExpr::Step(Box::new(TimeExpr::Var("t")))
+        ]))
+    }
+}
+
+impl Tf<{TfVar::Z}>
+{
+    /// Computes the inverse Z transform symbolically, as a sequence in `n`.
+    ///
+    /// The transfer function is read in powers of `z^-1`, and its denominator must factor into first- and second-order terms,
+    /// like for [inverse_laplace](Tf::inverse_laplace). Any polynomial part gives delayed unit impulses.
+    pub fn inverse_z(&self) -> Option<TimeExpr>
+    {
+        let n_var = || TimeExpr::Var("n");
+        let b = trim(&self.0);
+        let a = trim(&self.1);
+        if a.first().is_none_or(Zero::is_zero)
+        {
+            return None
+        }
+        let n = a.len() - 1;
+        let ar: Vec<Ratio> = a.iter()
+            .map(ratio)
+            .collect();
+
+        // B = Q*A + R
+        let mut r: Vec<Ratio> = b.iter()
+            .map(ratio)
+            .collect();
+        let mut q: Vec<Ratio> = vec![Zero::zero(); (r.len() + 1).saturating_sub(a.len())];
+        for i in (n..r.len()).rev()
+        {
+            let qi = &r[i]/&ar[n];
+            for j in 0..=n
+            {
+                r[i - n + j] = r[i - n + j].clone() - &(&qi*&ar[j]);
+            }
+            q[i - n] = qi;
+        }
+        r.resize(n, Zero::zero());
+
+        let mut terms = vec![];
+        for (i, q) in q.iter().enumerate()
+        {
+            if !Zero::is_zero(q)
+            {
+                terms.push(TimeExpr::product(vec![
+                    constant(q),
+                    TimeExpr::Delta(Box::new(TimeExpr::sum(vec![n_var(), TimeExpr::int(-(i as Int))])))
+                ]));
+            }
+        }
+
+        let causal = if n == 0
+        {
+            vec![]
+        }
+        else
+        {
+            let factors = factor(&a)?;
+            if let [a] = &factors[..]
+            {
+                z_terms(&r, a)
+            }
+            else
+            {
+                let groups: Vec<Vec<Ratio>> = factors.iter()
+                    .map(|f| f.iter().map(ratio).collect())
+                    .collect();
+                factors.iter()
+                    .zip(partial_fractions(&r, &groups)?)
+                    .flat_map(|(f, r)| z_terms(&r, f))
+                    .collect()
+            }
+        };
+        let causal = TimeExpr::sum(causal);
+        if !causal.is_zero()
+        {
+            terms.push(TimeExpr::product(vec![
+                causal,
+                TimeExpr::Step(Box::new(n_var()))
+            ]));
+        }
+
+        Some(TimeExpr::sum(terms))
+    }
+}
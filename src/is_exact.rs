@@ -0,0 +1,67 @@
+use num::Complex;
+
+use crate::{coefficient::Coefficient, weight::Weight};
+
+/// Whether arithmetic on a value is exact, so that rearranging sums doesn't change the result.
+pub trait IsExact
+{
+    fn is_exact(&self) -> bool;
+}
+
+macro_rules! impl_exact
+{
+    ($($t:ty),*) => {
+        $(
+            impl IsExact for $t
+            {
+                fn is_exact(&self) -> bool
+                {
+                    true
+                }
+            }
+        )*
+    };
+}
+impl_exact!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl IsExact for f32
+{
+    fn is_exact(&self) -> bool
+    {
+        false
+    }
+}
+
+impl IsExact for f64
+{
+    fn is_exact(&self) -> bool
+    {
+        false
+    }
+}
+
+impl<T> IsExact for Complex<T>
+where
+    T: IsExact
+{
+    fn is_exact(&self) -> bool
+    {
+        self.re.is_exact() && self.im.is_exact()
+    }
+}
+
+impl IsExact for Weight
+{
+    fn is_exact(&self) -> bool
+    {
+        !matches!(self, Weight::Float(_))
+    }
+}
+
+impl IsExact for Coefficient
+{
+    fn is_exact(&self) -> bool
+    {
+        self.0.0.values().all(IsExact::is_exact)
+    }
+}
@@ -10,6 +10,7 @@ pub mod transfer_function;
 pub mod polynomial;
 pub mod coefficient;
 pub mod weighted_sum;
+pub mod weight;
 pub mod sym;
 pub mod powered_product;
 pub mod simplify;
@@ -17,6 +18,28 @@ pub mod compute;
 pub mod partial_zero;
 pub mod partial_one;
 pub mod is_neg;
+pub mod is_exact;
+pub mod sos;
+pub mod iir_filter;
+pub mod time_response;
+pub mod inverse_transform;
+pub mod frequency_response;
+pub mod monte_carlo;
+pub mod horner;
+pub mod cse;
+pub mod normalize;
+pub mod interconnect;
+pub mod signal_flow;
+pub mod tf_matrix;
+pub mod prototype;
+pub mod frequency_transform;
+pub mod biquad;
+pub mod parallel;
+pub mod batch;
+pub mod context;
+pub mod parse;
+pub mod emit;
+pub mod project;
 
 use self::coefficient::Coefficient;
 use self::partial_one::PartialOne;
@@ -28,11 +51,11 @@ type Int = i128;
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Write};
+    use std::{collections::HashMap, fs::File, io::Write};
 
-    use num::{Complex, traits::{Inv, Pow}, One};
+    use num::{Complex, traits::{Inv, Pow}, One, Zero};
 
-    use crate::{polynomial::Polynomial, coefficient::Coefficient, partial_one::PartialOne, Tf};
+    use crate::{polynomial::Polynomial, coefficient::Coefficient, TfVar, partial_one::PartialOne, compute::Compute, sym::Sym, sos::SosOrder, monte_carlo::Tolerance, weight::Weight, iir_filter::{IirFilter, IirStructure}, interconnect::FeedbackSign, signal_flow::SignalFlowGraph, tf_matrix::TfMatrix, prototype::Prototype, biquad::{Biquad, biquad_syms}, batch::batch, parallel::with_threads, context::{Context, Interrupt}, parse::parse_number, emit::Format, project::{Project, ProjectError}, Tf};
 
     #[test]
     fn mul()
@@ -55,4 +78,888 @@ mod tests {
 
         println!("H(z) = {}", tfz);
     }
+
+    #[test]
+    fn sos()
+    {
+        let s = Tf::s(1);
+        let tf = Tf::from("omega")*"omega"*"omega"
+            /((s.clone()*s.clone() + s.clone()*"omega" + Tf::from("omega")*"omega")*(s + "omega"));
+        let tfz = tf.bilinear_transform();
+
+        let syms = HashMap::from([("omega", 2000.0), ("rate", 44100.0)]);
+        let (b, a) = tfz.compute(syms.clone()).unwrap();
+        let sos = tfz.sos(syms, SosOrder::Up).unwrap();
+        assert_eq!(sos.len(), 2);
+
+        for w in [0.0, 0.01, 0.1, 1.0, 3.0]
+        {
+            let x = Complex::from_polar(1.0, -w);
+            let h = b.horner(x)/a.horner(x);
+            let h_sos = sos.iter()
+                .map(|s| Polynomial(s[..3].to_vec()).horner(x)/Polynomial(s[3..].to_vec()).horner(x))
+                .product::<Complex<f64>>();
+            assert!((h - h_sos).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn iir_filter()
+    {
+        let s = Tf::s(1);
+        let tf = Tf::from("omega")*"omega"*"omega"
+            /((s.clone()*s.clone() + s.clone()*"omega" + Tf::from("omega")*"omega")*(s + "omega"));
+        let tfz = tf.bilinear_transform();
+
+        let syms = HashMap::from([("omega", 2000.0), ("rate", 44100.0)]);
+        let mut filters = [
+            IirStructure::DirectFormI,
+            IirStructure::DirectFormIITransposed,
+            IirStructure::Sos(SosOrder::Up)
+        ].map(|structure| IirFilter::new(&tfz, syms.clone(), structure).unwrap());
+
+        let mut x = [0.0f64; 64];
+        x[0] = 1.0;
+        let y = filters.each_mut()
+            .map(|filter| {
+                let mut y = x;
+                filter.process_block(&mut y);
+                y
+            });
+        for yn in y[1..].iter()
+        {
+            for (y0, y) in y[0].iter().zip(yn.iter())
+            {
+                assert!((y0 - y).abs() < 1e-12);
+            }
+        }
+
+        // Updating with the same coefficients continues as if nothing happened
+        let x: Vec<f64> = (0..64).map(|n| ((n*7 % 11) as f64 - 5.0)/5.0).collect();
+        for filter in filters.iter_mut()
+        {
+            filter.reset();
+            let mut reference = filter.clone();
+            let y: Vec<f64> = x.iter().map(|&x| reference.process_sample(x)).collect();
+            for (n, &x) in x.iter().enumerate()
+            {
+                if n == 32
+                {
+                    filter.update(&tfz, syms.clone()).unwrap();
+                }
+                assert!((filter.process_sample(x) - y[n]).abs() < 1e-12);
+            }
+        }
+
+        // Direct form I keeps its past inputs and outputs across new coefficients
+        let syms2 = HashMap::from([("omega", 4000.0), ("rate", 44100.0)]);
+        let coeffs = [syms, syms2.clone()].map(|syms| {
+            let (b, a) = tfz.compute(syms).unwrap();
+            let a0 = a.0[0];
+            (b.0.iter().map(|b| b/a0).collect::<Vec<f64>>(), a.0.iter().map(|a| a/a0).collect::<Vec<f64>>())
+        });
+        let mut y = vec![];
+        for n in 0..x.len()
+        {
+            let (b, a) = &coeffs[n/32];
+            let mut yn = (0..b.len()).filter(|&k| k <= n).map(|k| b[k]*x[n - k]).sum::<f64>();
+            yn -= (1..a.len()).filter(|&k| k <= n).map(|k| a[k]*y[n - k]).sum::<f64>();
+            y.push(yn);
+        }
+        let filter = &mut filters[0];
+        filter.reset();
+        for (n, &x) in x.iter().enumerate()
+        {
+            if n == 32
+            {
+                filter.update(&tfz, syms2.clone()).unwrap();
+            }
+            assert!((filter.process_sample(x) - y[n]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn impulse_response()
+    {
+        let s = Tf::s(1);
+        let syms = HashMap::from([("tau", 0.5), ("p", 0.5)]);
+        let t = [0.0, 0.1, 1.0, 2.5];
+
+        let h = (s.clone()*"tau" + 1).inv().impulse_response(syms.clone(), &t).unwrap();
+        for (h, t) in h.into_iter().zip(t)
+        {
+            assert!((h - (-t/0.5f64).exp()/0.5).abs() < 1e-9);
+        }
+
+        let h = ((s.clone()*"tau" + 1)*(s*"tau" + 1)).inv().step_response(syms.clone(), &t).unwrap();
+        for (h, t) in h.into_iter().zip(t)
+        {
+            assert!((h - (1.0 - (1.0 + t/0.5f64)*(-t/0.5f64).exp())).abs() < 1e-9);
+        }
+
+        let tf: Tf<{TfVar::Z}> = Tf(Polynomial::one(), Polynomial(vec![Coefficient::from(1), -Coefficient::from("p")]));
+        let h = tf.impulse_response(syms.clone(), 8).unwrap();
+        for (n, h) in h.into_iter().enumerate()
+        {
+            assert_eq!(h, 0.5f64.powi(n as i32));
+        }
+        let h = tf.step_response(syms, 8).unwrap();
+        assert!((h[7] - (2.0 - 0.5f64.powi(7))).abs() < 1e-12);
+    }
+
+    #[test]
+    fn same_denominator()
+    {
+        let s = Tf::s(1);
+        let a = (s.clone() + 1).inv();
+        let b = s.clone()/(s.clone() + 1);
+        assert_eq!(&a + &b, Tf(Polynomial(vec![Coefficient::from(1), Coefficient::from(1)]), a.1.clone()));
+        assert_eq!(&b - &a, Tf(Polynomial(vec![Coefficient::from(-1), Coefficient::from(1)]), a.1.clone()));
+
+        let mut h = b;
+        h.set_zero();
+        assert!(h.is_zero());
+        assert_eq!(h, Tf::zero());
+    }
+
+    #[test]
+    fn inverse_laplace()
+    {
+        let s = Tf::s(1);
+
+        let h = (s.clone()*"tau" + 1).inv().inverse_laplace().unwrap();
+        println!("h(t) = {}", h);
+
+        let t = [0.0, 0.1, 1.0, 2.5];
+        for (tf, zeta) in [
+            (Tf::from("omega")*"omega"/(s.clone()*s.clone() + s.clone()*"omega"*"zeta"*2 + Tf::from("omega")*"omega"), 0.3),
+            (Tf::from("omega")*"omega"/(s.clone()*s.clone() + s.clone()*"omega"*"zeta"*2 + Tf::from("omega")*"omega"), 2.0),
+            ((s.clone() + "omega")/(s.clone()*s.clone()*s.clone() + s.clone()*s.clone()*"omega"*"zeta"*2), 0.7),
+            (s.clone()/((s.clone()*"tau" + 1)*(s.clone()*"tau" + 1)), 0.0),
+            (Tf::from("omega")*"omega"*"alpha"/((s.clone()*s.clone() + s.clone()*"omega"*"zeta"*2 + Tf::from("omega")*"omega")*(s.clone() + "alpha")), 0.3),
+            ((Tf::from("omega")*"omega"/((s.clone()*s.clone() + s.clone()*"omega"*"zeta"*2 + Tf::from("omega")*"omega")*(s.clone() + "alpha")))
+                .partial_eval(HashMap::from([("omega", 3.0), ("zeta", 0.3), ("alpha", 1.5)])), 0.3),
+            (s.clone()/((s.clone() + "alpha")*(s.clone() + "alpha")*(s.clone() + 2)), 0.0),
+            ((s.clone() + 3)/(s.clone()*s.clone()*(s.clone() + 1)*(s.clone() + 2)), 0.0),
+            (((s.clone() + 1)*(s.clone() + 1)*(s.clone() + 2)).inv(), 0.0),
+            (((s.clone() + 2)*(s.clone() + 1)*(s.clone() + 1)*(s.clone() + 3)).inv(), 0.0)
+        ]
+        {
+            let syms = HashMap::from([("omega", 3.0f64), ("zeta", zeta), ("tau", 0.5), ("alpha", 1.5)]);
+            let h = tf.inverse_laplace().unwrap();
+            println!("h(t) = {}", h);
+            let expected = tf.impulse_response(syms.clone(), &t).unwrap();
+            for (t, expected) in t.into_iter().zip(expected)
+            {
+                let mut syms = syms.clone();
+                syms.insert("t", t);
+                assert!((h.compute(syms).unwrap() - expected).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_z()
+    {
+        let a = |a1: &'static str, a2: &'static str| Polynomial(vec![Coefficient::from(1), Coefficient::from(a1), Coefficient::from(a2)]);
+        for (tf, a1, a2) in [
+            (Tf::<{TfVar::Z}>(Polynomial(vec![Coefficient::from(1), Coefficient::from("b1")]), a("a1", "a2")), -1.2, 0.5),
+            (Tf(Polynomial(vec![Coefficient::from(1), Coefficient::from("b1"), Coefficient::from(1), Coefficient::from("b1")]), a("a1", "a2")), 0.7, -0.3),
+            (Tf(Polynomial(vec![Coefficient::from("b1")]), Polynomial(vec![Coefficient::from(1), -Coefficient::from("a1")])), 0.8, 0.0),
+            (Tf(Polynomial(vec![Coefficient::from(1), Coefficient::from("b1")]), a("a1", "a2")*Polynomial(vec![Coefficient::from(1), -Coefficient::from("p")])), -1.2, 0.5),
+            (Tf(Polynomial(vec![Coefficient::from(1)]), a("a1", "a2")*Polynomial(vec![Coefficient::from(1), -Coefficient::from("p")]))
+                .partial_eval(HashMap::from([("a1", 0.7), ("a2", 0.3), ("p", 0.5)])), 0.7, 0.3)
+        ]
+        {
+            let syms = HashMap::from([("b1", 0.25f64), ("a1", a1), ("a2", a2), ("p", 0.5)]);
+            let h = tf.inverse_z().unwrap();
+            println!("h[n] = {}", h);
+            let expected = tf.impulse_response(syms.clone(), 16).unwrap();
+            for (n, expected) in expected.into_iter().enumerate()
+            {
+                let mut syms = syms.clone();
+                syms.insert("n", n as f64);
+                assert!((h.compute(syms).unwrap() - expected).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn sensitivity()
+    {
+        let s = Tf::s(1);
+        let h = (s.clone()*"C"*"R" + 1).inv()*"G";
+
+        let dh = h.diff_sym(Sym("R"));
+        let sh = h.sensitivity(Sym("R"));
+        println!("dH/dR = {}", dh);
+        println!("S_R^H = {}", sh);
+
+        let syms = HashMap::from([("R", 1e3f64), ("C", 1e-6), ("G", 2.0)]);
+        for omega in [10.0, 1e3, 1e5]
+        {
+            let dr = 1e-3;
+            let mut syms_r0 = syms.clone();
+            let mut syms_r1 = syms.clone();
+            syms_r0.insert("R", 1e3 - dr);
+            syms_r1.insert("R", 1e3 + dr);
+            let h0 = h.frequency_response(syms.clone(), omega).unwrap();
+            let dh_num = (h.frequency_response(syms_r1, omega).unwrap() - h.frequency_response(syms_r0, omega).unwrap())/(2.0*dr);
+            let dh = dh.frequency_response(syms.clone(), omega).unwrap();
+            assert!((dh_num - dh).norm() < 1e-6*dh.norm());
+
+            let sh = sh.frequency_response(syms.clone(), omega).unwrap();
+            assert!((sh - dh*1e3/h0).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn monte_carlo()
+    {
+        let s = Tf::s(1);
+        let h = (s*"C"*"R" + 1).inv();
+
+        let nominal = HashMap::from([("R", 1e3f64), ("C", 1e-6)]);
+        let tolerances = HashMap::from([("R", Tolerance::Uniform(0.05)), ("C", Tolerance::Gaussian(0.1/3.0))]);
+        let omega = [0.0, 1e3, 1e4];
+
+        let mc = h.monte_carlo(nominal.clone(), &tolerances, &omega, 1000, 1).unwrap();
+        assert_eq!(mc, h.monte_carlo(nominal.clone(), &tolerances, &omega, 1000, 1).unwrap());
+
+        let min = mc.magnitude.min();
+        let max = mc.magnitude.max();
+        let median = mc.magnitude.percentile(50.0);
+        assert!((min[0] - 1.0).abs() < 1e-12 && (max[0] - 1.0).abs() < 1e-12);
+        for i in 1..omega.len()
+        {
+            let h0 = h.frequency_response(nominal.clone(), omega[i]).unwrap();
+            assert!(min[i] < h0.norm() && h0.norm() < max[i]);
+            assert!((median[i] - h0.norm()).abs() < 0.02*h0.norm());
+            assert!(mc.phase.min()[i] < h0.arg() && h0.arg() < mc.phase.max()[i]);
+        }
+    }
+
+    #[test]
+    fn substitute()
+    {
+        let s = Tf::s(1);
+        let h = (s.clone()*"C"*"R" + 1).inv();
+
+        let hz = h.clone()
+            .bilinear_transform()
+            .substitute(Sym("rate"), &(Tf::from(1)/"T"));
+        println!("H(z) = {}", hz);
+        let syms = HashMap::from([("R", 1e3f64), ("C", 1e-6), ("T", 1.0/44100.0), ("rate", 44100.0)]);
+        for omega in [0.0, 0.1, 1.0]
+        {
+            let expected = h.clone().bilinear_transform().frequency_response(syms.clone(), omega).unwrap();
+            assert!((hz.frequency_response(syms.clone(), omega).unwrap() - expected).norm() < 1e-9);
+        }
+
+        let hc = h.substitute(Sym("R"), &(s.clone()*"C2").inv());
+        println!("H(s) = {}", hc);
+        let syms = HashMap::from([("C", 1e-6f64), ("C2", 3e-6)]);
+        assert!((hc.frequency_response(syms, 1e3).unwrap() - Complex::from(0.75)).norm() < 1e-12);
+
+        let (num, den) = (Coefficient::from("p_tb")*Coefficient::from("R") + Coefficient::from(1)/Coefficient::from("p_tb"))
+            .substitute(Sym("p_tb"), &(Coefficient::from(1) - Coefficient::from("p_tc")));
+        let syms = HashMap::from([("R", 2.0f64), ("p_tc", 0.25)]);
+        assert!((num.compute(syms.clone()).unwrap()/den.compute(syms).unwrap() - (1.5 + 1.0/0.75)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn partial_eval()
+    {
+        let s = Tf::s(1);
+        let h = (Tf::from("omega")*"omega"/(s.clone()*s.clone() + s.clone()*"omega"*"zeta"*2 + Tf::from("omega")*"omega"))
+            .bilinear_transform();
+
+        let bound = HashMap::from([("omega", 6283.2f64), ("rate", 44100.0)]);
+        let hp = h.partial_eval(bound.clone());
+        println!("H(z) = {}", hp);
+        let terms = |h: &Tf<{TfVar::Z}>| h.0.0.iter().chain(h.1.0.iter()).map(|c| c.0.0.len()).sum::<usize>();
+        assert!(terms(&hp) < terms(&h));
+
+        let mut syms = bound.clone();
+        syms.insert("zeta", 0.3);
+        for omega in [0.0, 0.1, 1.0]
+        {
+            let expected = h.frequency_response(syms.clone(), omega).unwrap();
+            let hp = hp.frequency_response(HashMap::from([("zeta", 0.3)]), omega).unwrap();
+            assert!((hp - expected).norm() < 1e-9*expected.norm());
+        }
+
+        let hi = h.partial_eval(HashMap::from([("rate", 48000.0f64)]));
+        assert!(hi.0.0.iter().chain(hi.1.0.iter()).all(|c| c.0.0.values().all(|w| matches!(w, Weight::Int(_)))));
+
+        // Inexact division stays exact
+        let third = Coefficient::from("x")/Coefficient::from(3);
+        assert!(third.0.0.values().all(|w| matches!(w, Weight::Ratio(_))));
+        assert_eq!(third.clone()*Coefficient::from(3), Coefficient::from("x"));
+        assert_eq!(third.to_string(), "x*(1.0/3.0)");
+        assert_ne!(Weight::Float(f64::NAN), Weight::Float(f64::NAN));
+    }
+
+    #[test]
+    fn horner()
+    {
+        let c = |s: &'static str| Coefficient::from(s);
+
+        let x = c("a")*c("x")*c("x") + c("b")*c("x") + c("c");
+        assert_eq!(format!("{}", x.horner()), "c + x*(b + x*a)");
+
+        let x = (c("a")*c("x")*c("x")*c("x") - c("b")*c("x") + Coefficient::from(2))*c("r")*c("r")/c("k");
+        assert_eq!(format!("{}", x.horner()), "1.0/k*r^2*(2.0 + x*(-b + x^2*a))");
+
+        let h = Tf::s(2)/(Tf::s(2) + Tf::s(1)*"omega"*2*"zeta" + Tf::from("omega")*"omega");
+        let a = &h.bilinear_transform().1.0[0];
+        assert_eq!(format!("{}", a.horner()), "4.0*rate^2 + omega*(4.0*rate*zeta + omega)");
+    }
+
+    #[test]
+    fn cse()
+    {
+        let s = Tf::s(1);
+        let a = (s.clone()*s.clone() + s.clone()*2*"zeta"*"omega" + Tf::from("omega")*"omega")*(s.clone() + "alpha");
+        let h = [
+            Tf::from("k")*"k"*"k",
+            s.clone()*"k"*"k",
+            s.clone()*s.clone()*"k"
+        ].map(|b| (b/a.clone()).bilinear_transform());
+
+        let cse = Tf::cse(&h, "t");
+        assert!(cse.exprs.iter().all(|h| h.1 == cse.exprs[0].1 && h.1.0.iter().all(|c| c.0.0.len() == 1)));
+
+        let mut syms = HashMap::from([("k", 0.7f64), ("rate", 44100.0), ("alpha", 300.0), ("omega", 1000.0), ("zeta", 0.3)]);
+        for (t, c) in cse.assignments.iter()
+        {
+            let x = c.compute(syms.clone()).unwrap();
+            syms.insert(t.0, x);
+        }
+        for (h, h_cse) in h.iter().zip(cse.exprs.iter())
+        {
+            let (b, a) = h.compute(syms.clone()).unwrap();
+            let (b_cse, a_cse) = h_cse.compute(syms.clone()).unwrap();
+            for (x, y) in b.0.iter().chain(a.0.iter()).zip(b_cse.0.iter().chain(a_cse.0.iter()))
+            {
+                assert!((x - y).abs() <= 1e-9*x.abs());
+            }
+        }
+
+        // A product of a symbol and a shared sum is only assigned after the sum
+        let c = |s: &'static str| Coefficient::from(s);
+        let exprs = [
+            c("rate")*c("a") + c("rate")*c("b"),
+            c("k")*c("rate")*c("a") + c("k")*c("rate")*c("b")
+        ];
+        let cse = Coefficient::cse(exprs.iter(), "t");
+        let mut syms = HashMap::from([("rate", 48000.0f64), ("a", 0.3), ("b", -1.7), ("k", 2.5)]);
+        for (t, c) in cse.assignments.iter()
+        {
+            syms.insert(t.0, c.compute(syms.clone()).unwrap());
+        }
+        for (c, c_cse) in exprs.iter().zip(cse.exprs.iter())
+        {
+            let x: f64 = c.compute(syms.clone()).unwrap();
+            assert!((x - c_cse.compute(syms.clone()).unwrap()).abs() <= 1e-9*x.abs());
+        }
+    }
+
+    #[test]
+    fn normalize()
+    {
+        let s = Tf::s(1);
+        let x = s.clone()*2/Tf::from(2);
+        assert_ne!(x, s);
+        assert!(x.equivalent(&s));
+        assert_eq!(x.canonical(), s.canonical());
+        assert_eq!((-x.clone()).canonical(), Tf(x.0.clone(), -x.1.clone()).canonical());
+
+        let a = s.clone() + "alpha";
+        let b = s.clone() + "beta";
+        assert!((a.clone()/(a.clone()*b.clone())).equivalent(&(Tf::from(1)/b.clone())));
+        assert!(!(a.clone()/b.clone()).equivalent(&(b.clone()/a.clone())));
+
+        let h = (Tf::from("k")/(s.clone()*2*"tau")).normalize_monic().unwrap();
+        assert_eq!(h.den, Coefficient::from(1));
+        assert!(One::is_one(&h.a.0[1]) && h.a.0[0].trim().0.0.is_empty());
+
+        let h = (Tf::from(1)/(s.clone()*"tau" + 1)).bilinear_transform();
+        let h_a0 = h.normalize_a0().unwrap();
+        assert_eq!(h_a0.inv, Some(Sym("inv")));
+        assert!(One::is_one(&h_a0.a.0[0]));
+        assert!(h_a0.b.0.iter().chain(h_a0.a.0[1..].iter())
+            .all(|c| c.0.0.keys().all(|e| e.0.contains_key(&Sym("inv")))));
+        let syms = HashMap::from([("tau", 1e-3f64), ("rate", 44100.0)]);
+        let (b, a) = h.compute(syms.clone()).unwrap();
+        let (b_a0, a_a0) = h_a0.compute(syms).unwrap();
+        assert_eq!(a_a0.0[0], 1.0);
+        for (x, y) in b.0.iter().chain(a.0.iter()).zip(b_a0.0.iter().chain(a_a0.0.iter()))
+        {
+            assert!((x/a.0[0] - y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn feedback()
+    {
+        let s = Tf::s(1);
+        let g = Tf::from("k")/(s.clone()*"tau" + 1);
+        let h = Tf::from("beta");
+
+        let y = g.feedback(&h, FeedbackSign::Negative);
+        assert!(y.equivalent(&(Tf::from("k")/(s.clone()*"tau" + 1 + Tf::from("k")*"beta"))));
+        let y = g.feedback(&h, FeedbackSign::Positive);
+        assert!(y.equivalent(&(Tf::from("k")/(s.clone()*"tau" + 1 - Tf::from("k")*"beta"))));
+        let y = g.unity_feedback(FeedbackSign::Negative);
+        assert!(y.equivalent(&(Tf::from("k")/(s.clone()*"tau" + 1 + "k"))));
+
+        assert!(g.series(&h).equivalent(&(g.clone()*h.clone())));
+        assert!(g.parallel(&h).equivalent(&(g.clone() + h.clone())));
+
+        // Delay line with regeneration, mixed with the dry signal
+        let delay = Tf::<{TfVar::Z}>(Polynomial(vec![Coefficient::from(0), Coefficient::from(1)]), Polynomial(vec![Coefficient::from(1)]));
+        let wet = delay.feedback(&Tf::from("g"), FeedbackSign::Positive);
+        let y = wet.parallel(&Tf::from(1));
+        let syms = HashMap::from([("g", 0.5f64)]);
+        let h = y.impulse_response(syms, 4).unwrap();
+        assert_eq!(h, vec![1.0, 1.0, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn signal_flow()
+    {
+        let g = Tf::<{TfVar::S}>::from("g");
+        let h = Tf::from("h");
+
+        let mut sfg = SignalFlowGraph::new();
+        sfg.add_branch(0, 1, One::one());
+        sfg.add_branch(1, 2, g.clone());
+        sfg.add_branch(2, 1, h.clone());
+        assert_eq!(sfg.loops(), vec![vec![1, 2]]);
+        assert!(sfg.gain(0, 2).equivalent(&g.feedback(&h, FeedbackSign::Positive)));
+
+        // Two non-touching loops, and a path bypassing the second
+        let s = Tf::s(1);
+        let g2 = Tf::from(1)/(s.clone()*"tau" + 1);
+        let h2 = Tf::from("f");
+        sfg.add_branch(2, 3, One::one());
+        sfg.add_branch(3, 4, g2.clone());
+        sfg.add_branch(4, 3, h2.clone());
+        sfg.add_branch(2, 4, Tf::from("d"));
+        assert_eq!(sfg.loops().len(), 2);
+        assert_eq!(sfg.non_touching_loops()[1], vec![vec![0, 1]]);
+        assert_eq!(sfg.forward_paths(0, 4).len(), 2);
+
+        let y = g.feedback(&h, FeedbackSign::Positive)*(g2.clone() + "d")/(Tf::from(1) - g2.clone()*h2.clone());
+        assert!(sfg.gain(0, 4).equivalent(&y));
+    }
+
+    #[test]
+    fn tf_matrix()
+    {
+        let s = Tf::s(1);
+        let g = TfMatrix(vec![
+            vec![Tf::from(1)/(s.clone() + "a"), Zero::zero()],
+            vec![Tf::from("k"), Tf::from(1)/(s.clone() + "b")]
+        ]);
+
+        let i = &g*&g.inverse().unwrap();
+        for (j, row) in i.0.iter().enumerate()
+        {
+            for (k, h) in row.iter().enumerate()
+            {
+                assert!(h.equivalent(&if j == k {Tf::from(1)} else {Zero::zero()}));
+            }
+        }
+
+        let y = g.feedback(&TfMatrix::identity(2), FeedbackSign::Negative).unwrap();
+        assert!(y.0[0][0].equivalent(&g.0[0][0].unity_feedback(FeedbackSign::Negative)));
+        assert!(y.0[1][1].equivalent(&g.0[1][1].unity_feedback(FeedbackSign::Negative)));
+
+        let x = TfMatrix::column(vec![Tf::from("u"), Tf::from("v")]);
+        let y = &g*&x;
+        assert!(y.0[1][0].equivalent(&(Tf::from("k")*"u" + Tf::from("v")/(s.clone() + "b"))));
+        assert_eq!(g.checked_mul(&x), Some(y));
+        assert_eq!(x.checked_mul(&x), None);
+        assert_eq!(g.checked_add(&x), None);
+        assert_eq!(g.checked_sub(&x), None);
+        assert!(g.feedback(&x, FeedbackSign::Negative).is_none());
+
+        let ragged = TfMatrix(vec![vec![Tf::from(1), Tf::from("k")], vec![Tf::from(1)]]);
+        assert!(!ragged.is_rectangular());
+        assert_eq!(ragged.checked_mul(&g), None);
+        assert_eq!(g.checked_mul(&ragged), None);
+        assert_eq!(ragged.checked_add(&g), None);
+        assert_eq!(g.checked_sub(&ragged), None);
+        assert_eq!(ragged.determinant(), None);
+        assert_eq!(ragged.inverse(), None);
+        assert!(ragged.feedback(&TfMatrix::identity(2), FeedbackSign::Negative).is_none());
+
+        let gz = g.clone().bilinear_transform();
+        assert_eq!(gz.0[0][0], g.0[0][0].clone().bilinear_transform());
+    }
+
+    #[test]
+    fn z_construction()
+    {
+        assert_eq!(Tf::z(-2), Tf::delay(2));
+        assert!((Tf::z(1)*Tf::delay(1)).equivalent(&Tf::from(1)));
+
+        let taps = [0.5, 0.25, -0.125];
+        let h = Tf::fir(&taps.map(Coefficient::from));
+        assert_eq!(h.impulse_response(HashMap::<_, f64>::new(), 4).unwrap(), vec![0.5, 0.25, -0.125, 0.0]);
+
+        let h = Tf::iir(&["b0", "b1"].map(Coefficient::from), &[Coefficient::from(1), Coefficient::from("a1")]);
+        assert_eq!(format!("{}", h.z_inv()), "(b0 + b1*z^-1)/(1.0 + a1*z^-1)");
+        assert_eq!((Coefficient::from("b0")*Coefficient::from(1.0)).to_string(), "b0");
+        assert_eq!((Coefficient::from("b0")*Coefficient::from(2.0)).to_string(), "b0*(2.0)");
+        let syms = HashMap::from([("b0", 1.0f64), ("b1", 0.0), ("a1", -0.5)]);
+        assert_eq!(h.impulse_response(syms, 3).unwrap(), vec![1.0, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn prototype()
+    {
+        let db = |h: Complex<f64>| 20.0*h.norm().log10();
+        let syms = HashMap::from([("omega", 2.0f64)]);
+
+        let h = Tf::prototype(Prototype::Butterworth, 5, Coefficient::from("omega")).unwrap();
+        assert!((db(h.frequency_response(syms.clone(), 2.0).unwrap()) + 3.0103).abs() < 1e-3);
+
+        let h = Tf::prototype(Prototype::Bessel, 3, Coefficient::from(1.0)).unwrap();
+        let (_, a) = h.compute(HashMap::<_, f64>::new()).unwrap();
+        for (a, a_ref) in a.0.iter().zip([1.0, 2.46621, 2.43288, 1.0])
+        {
+            assert!((a - a_ref).abs() < 1e-4);
+        }
+
+        for order in [4, 5]
+        {
+            let h = Tf::prototype(Prototype::ChebyshevI {ripple: 1.0}, order, Coefficient::from("omega")).unwrap();
+            assert!((db(h.frequency_response(syms.clone(), 2.0).unwrap()) + 1.0).abs() < 1e-6);
+            assert!(db(h.frequency_response(syms.clone(), 0.0).unwrap()) > -1.0 - 1e-6);
+
+            let h = Tf::prototype(Prototype::ChebyshevII {attenuation: 40.0}, order, Coefficient::from("omega")).unwrap();
+            assert!((db(h.frequency_response(syms.clone(), 2.0).unwrap()) + 40.0).abs() < 1e-6);
+            assert!(db(h.frequency_response(syms.clone(), 0.0).unwrap()).abs() < 1e-6);
+
+            let h = Tf::prototype(Prototype::Elliptic {ripple: 1.0, attenuation: 40.0}, order, Coefficient::from("omega")).unwrap();
+            assert!((db(h.frequency_response(syms.clone(), 2.0).unwrap()) + 1.0).abs() < 1e-6);
+            for i in 0..100
+            {
+                let omega = 2.0*i as f64/100.0;
+                assert!(db(h.frequency_response(syms.clone(), omega).unwrap()) > -1.0 - 1e-6);
+                assert!(db(h.frequency_response(syms.clone(), 2.0*omega + 6.0).unwrap()) < -40.0 + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn frequency_transform()
+    {
+        let db = |h: Complex<f64>| 20.0*h.norm().log10();
+        let syms = HashMap::from([("omega", 10.0f64), ("bw", 2.0)]);
+        let omega = || Coefficient::from("omega");
+
+        let lp = Tf::prototype(Prototype::Butterworth, 2, Coefficient::from(1.0)).unwrap();
+        assert!(lp.scale_frequency(omega()).equivalent(&Tf::prototype(Prototype::Butterworth, 2, omega()).unwrap()));
+
+        let hp = lp.to_highpass(omega());
+        assert_eq!(hp.order(), 2);
+        assert!((db(hp.frequency_response(syms.clone(), 10.0).unwrap()) + 3.0103).abs() < 1e-3);
+        assert!(db(hp.frequency_response(syms.clone(), 1.0).unwrap()) < -39.0);
+
+        let bp = lp.to_bandpass(omega(), Coefficient::from("bw"));
+        assert_eq!(bp.order(), 4);
+        assert!(db(bp.frequency_response(syms.clone(), 10.0).unwrap()).abs() < 1e-9);
+        let edge = (10.0f64*10.0 + 1.0).sqrt();
+        for omega in [edge - 1.0, edge + 1.0]
+        {
+            assert!((db(bp.frequency_response(syms.clone(), omega).unwrap()) + 3.0103).abs() < 1e-3);
+        }
+
+        let bs = lp.to_bandstop(omega(), Coefficient::from("bw"));
+        assert_eq!(bs.order(), 4);
+        assert!(bs.frequency_response(syms.clone(), 10.0).unwrap().norm() < 1e-9);
+        assert!(db(bs.frequency_response(syms.clone(), 0.0).unwrap()).abs() < 1e-9);
+
+        // A symbol named `s` in the coefficients is kept apart from the variable
+        let h = Tf::from(1)/(Tf::s(1) + "s");
+        assert!(h.scale_frequency(omega()).equivalent(&(Tf::from("omega")/(Tf::s(1) + Tf::from("s")*"omega"))));
+    }
+
+    #[test]
+    fn biquad()
+    {
+        let syms = biquad_syms(1000.0f64, 44100.0, 0.7, 6.0);
+        for biquad in [Biquad::Lowpass, Biquad::Highpass, Biquad::Bandpass, Biquad::Notch, Biquad::Allpass, Biquad::Peaking, Biquad::LowShelf, Biquad::HighShelf]
+        {
+            let hz = Tf::<{TfVar::Z}>::biquad(biquad);
+            let hs = Tf::<{TfVar::S}>::biquad(biquad, Coefficient::from("omega")).bilinear_transform();
+            let (bz, az) = hz.compute(syms.clone()).unwrap();
+            let (bs, as_) = hs.compute(syms.clone()).unwrap();
+            for (x, y) in bz.0.iter().chain(az.0.iter()).zip(bs.0.iter().chain(as_.0.iter()))
+            {
+                assert!((x/az.0[0] - y/as_.0[0]).abs() < 1e-9, "{:?}", biquad);
+            }
+        }
+    }
+
+    #[test]
+    fn karatsuba()
+    {
+        let schoolbook = |a: &Polynomial<Coefficient>, b: &Polynomial<Coefficient>| {
+            let mut y = vec![Coefficient::from(0); a.0.len() + b.0.len() - 1];
+            for (i, a) in a.0.iter().enumerate()
+            {
+                for (j, b) in b.0.iter().enumerate()
+                {
+                    y[i + j] += a.clone()*b.clone();
+                }
+            }
+            Polynomial(y)
+        };
+        let p = |n: i128, k: i128| Polynomial((0..n)
+            .map(|i| Coefficient::from("x")*Coefficient::from(i + 1) + Coefficient::from("y")*Coefficient::from((i*k) % 7 - 3))
+            .collect::<Vec<_>>());
+
+        for (a, b) in [(p(70, 1), p(45, 2)), (p(33, 3), p(200, 5)), (p(64, 4), p(64, 6))]
+        {
+            assert_eq!(&a*&b, schoolbook(&a, &b));
+        }
+
+        let a = Polynomial((0..100).map(|i| (i as f64*0.37).sin()).collect::<Vec<_>>());
+        let b = Polynomial((0..77).map(|i| (i as f64*0.11).cos()).collect::<Vec<_>>());
+        let y = &a*&b;
+        for (n, y) in y.0.iter().enumerate()
+        {
+            // Summed along the shorter factor, like the schoolbook method
+            let y_ref: f64 = (0..=n).filter(|&j| j < b.0.len() && n - j < a.0.len()).map(|j| b.0[j]*a.0[n - j]).sum();
+            assert_eq!(*y, y_ref);
+        }
+
+        // Float weights take the schoolbook method, so that the rounding is the same
+        let p = |n: i128, k: f64| Polynomial((0..n)
+            .map(|i| Coefficient::from("x")*Coefficient::from((i as f64*k).sin()) + Coefficient::from("y")*Coefficient::from(1.0 + i as f64*1e-3))
+            .collect::<Vec<_>>());
+        for (a, b) in [(p(33, 0.11), p(40, 0.37)), (p(64, 0.5), p(64, 0.7))]
+        {
+            let y_ref = (0..a.0.len() + b.0.len() - 1)
+                .map(|n| (n.saturating_sub(b.0.len() - 1)..(n + 1).min(a.0.len()))
+                    .map(|i| &a.0[i]*&b.0[n - i])
+                    .sum::<Coefficient>()
+                ).collect::<Vec<_>>();
+            assert_eq!((&a*&b).0, y_ref);
+        }
+
+        // Primitive integers take Karatsuba's method too
+        let a = Polynomial((0..40i64).map(|i| i % 5 - 2).collect::<Vec<_>>());
+        let b = Polynomial((0..50i64).map(|i| i % 3 - 1).collect::<Vec<_>>());
+        let y_ref: Vec<i64> = (0..a.0.len() + b.0.len() - 1)
+            .map(|n| (n.saturating_sub(b.0.len() - 1)..(n + 1).min(a.0.len()))
+                .map(|i| a.0[i]*b.0[n - i])
+                .sum()
+            ).collect();
+        assert_eq!((&a*&b).0, y_ref);
+        assert_eq!((Polynomial(vec![1i32, 1])*Polynomial(vec![-1i32, 1])).0, vec![-1, 0, 1]);
+    }
+
+    #[test]
+    fn parallel()
+    {
+        let run = || {
+            let s = Tf::s(1);
+            let h = (0..6).fold(Tf::from(1), |h, i| h*(s.clone()*["R1", "R2", "R3"][i % 3]*["C1", "C2"][i % 2] + 1))
+                .bilinear_transform();
+            let a = Polynomial((0..300).map(|i| (i as f64*0.37).sin()).collect::<Vec<_>>());
+            let b = Polynomial((0..200).map(|i| (i as f64*0.11).cos()*1e-3).collect::<Vec<_>>());
+            (h, &a*&b)
+        };
+
+        let serial = with_threads(1, run);
+        let parallel = with_threads(4, run);
+
+        assert_eq!(serial.0, parallel.0);
+        assert!(serial.1.0.iter().zip(parallel.1.0.iter()).all(|(x, y)| x.to_bits() == y.to_bits()));
+    }
+
+    #[test]
+    fn batch_order()
+    {
+        let s = Tf::s(1);
+        let h: Vec<_> = (1..=6).map(|n| (0..n).fold(Tf::from(1), |h, i| h*(s.clone()*["R1", "R2"][i % 2]*"C" + 1))).collect();
+
+        let (z, c, n) = with_threads(4, || (
+            Tf::bilinear_transform_batch(&h),
+            Tf::coefficients_batch(&h),
+            batch(&h, |h| h.order())
+        ));
+
+        for (i, h) in h.iter().enumerate()
+        {
+            assert_eq!(z[i].value, h.clone().bilinear_transform());
+            assert_eq!(c[i].value.1, h.1.0.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+            assert_eq!(n[i].value, i + 1);
+        }
+    }
+
+    #[test]
+    fn context()
+    {
+        let s = Tf::s(1);
+        let h = || (0..6).fold(Tf::from(1), |h, i| h*(s.clone()*["R1", "R2", "R3"][i % 3]*["C1", "C2"][i % 2] + 1))
+            .bilinear_transform();
+
+        let progress = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let reports = progress.clone();
+        let context = Context::new()
+            .on_progress(std::time::Duration::ZERO, move |p| reports.lock().unwrap().push(p));
+        assert_eq!(context.run(h), Ok(h()));
+        let progress = progress.lock().unwrap();
+        assert!(progress.windows(2).all(|p| p[0].terms <= p[1].terms));
+        assert_eq!(progress.last().unwrap().degree, 6);
+
+        let context = Context::new().with_budget(100);
+        assert_eq!(context.run(h), Err(Interrupt::BudgetExceeded {budget: 100}));
+        assert!(context.progress().terms > 100);
+
+        // Stopped operations go on with zero coefficients, which later steps must get through without panicking
+        let context = Context::new().with_budget(10);
+        let syms = HashMap::from([("R1", 1e3), ("R2", 2e3), ("R3", 3e3), ("C1", 1e-6), ("C2", 2e-6), ("rate", 48e3)]);
+        assert_eq!(context.run(|| {
+            let h = h();
+            let y = (&h/&(h.clone() + Tf::from(1))).canonical();
+            y.normalize_a0().and_then(|y| y.compute(syms.clone()))
+        }).map(|_| ()), Err(Interrupt::BudgetExceeded {budget: 10}));
+        assert!(context.progress().terms < 100);
+
+        // Interrupted in the worker threads of a batch
+        let hs = vec![s.clone()*"R1" + 1; 8];
+        let context = Context::new();
+        context.cancel();
+        assert_eq!(with_threads(4, || context.run(|| Tf::bilinear_transform_batch(&hs))), Err(Interrupt::Cancelled));
+    }
+
+    #[test]
+    fn parse()
+    {
+        let s = Tf::s(1);
+        let h: Tf<{TfVar::S}> = "G/(s*R1*C1 + 1) - 2*s^2/(s + 1)^-1".parse().unwrap();
+        assert_eq!(h, Tf::from("G")/(s.clone()*"R1"*"C1" + 1) - s.clone()*s.clone()*2*(s.clone() + 1));
+
+        assert_eq!(parse_number("2.2k"), Some(Coefficient::from(2200)));
+        assert_eq!(parse_number("10M"), Some(Coefficient::from(10000000)));
+        assert_eq!(parse_number("4.7n"), Some(Coefficient::from(4.7e-9)));
+        assert_eq!(parse_number("1e-3"), Some(Coefficient::from(0.001)));
+        assert_eq!(parse_number("10kOhm"), None);
+        assert_eq!(parse_number("-1"), Some(Coefficient::from(-1)));
+        assert_eq!(parse_number("+2.2k"), Some(Coefficient::from(2200)));
+        assert_eq!(parse_number("-4.7n"), Some(Coefficient::from(-4.7e-9)));
+        assert_eq!(parse_number("--1"), None);
+
+        assert_eq!("2s".parse::<Tf<{TfVar::S}>>().unwrap_err().pos, 1);
+        assert!("(s + 1".parse::<Tf<{TfVar::S}>>().is_err());
+        assert!("s^x".parse::<Tf<{TfVar::S}>>().is_err());
+        assert_eq!("s\u{a0}+ 1".parse::<Tf<{TfVar::S}>>().unwrap(), s.clone() + 1);
+        assert!("1e2147483647k".parse::<Tf<{TfVar::S}>>().is_err());
+        assert!("1.25e-2147483647".parse::<Tf<{TfVar::S}>>().is_err());
+    }
+
+    #[test]
+    fn project()
+    {
+        let mut project: Project = "
+            [params]
+            R = 10k [1k..100k]
+            C = 10n
+            rate = 48k
+
+            [defs]
+            z = R + 1/(s*C)  # series RC
+
+            [tf lowpass]
+            expr = 1/(s*C*z)
+            method = bilinear
+            prewarp = 1k
+            bind = *
+            format = json
+
+            [tf symbolic]
+            expr = 1/(s*C*z)
+        ".parse().unwrap();
+        assert_eq!(project.targets.len(), 2);
+        assert_eq!(project.targets[1].tf, "1/(s*C*(R + 1/(s*C)))".parse().unwrap());
+        assert!(project.set("R", 1.0).is_err());
+        project.set("R", 1e3).unwrap();
+
+        let outputs = project.generate().unwrap();
+        assert_eq!(outputs[1].value.text, project.targets[1].tf.emit(Format::Text));
+        let h = project.targets[0].tf.prewarp(1e3, 48e3).unwrap()
+            .bilinear_transform()
+            .partial_eval(HashMap::from([("R", 1e3), ("C", 10e-9), ("rate", 48e3)]));
+        assert_eq!(outputs[0].value.text, h.emit(Format::Json));
+
+        // `^` is exclusive or in Rust
+        let h: Tf<{TfVar::S}> = "1/(s^2*R*C + s*R + 1)".parse().unwrap();
+        let rust = h.bilinear_transform().emit(Format::Rust);
+        assert!(!rust.contains('^') && rust.contains("C*R*rate.powi(2)"));
+        let h: Tf<{TfVar::S}> = "1/(s^3*R^12/C^2 + 1)".parse().unwrap();
+        assert!(h.emit(Format::Rust).ends_with("let a = [\n    C.powi(2),\n    0.0,\n    0.0,\n    R.powi(12),\n];"));
+
+        assert!(project.targets[0].tf.prewarp(0.0, 48e3).is_none());
+        assert!(project.targets[0].tf.prewarp(24e3, 48e3).is_none());
+        project.set("rate", 1.5e3).unwrap();
+        assert!(matches!(project.generate(), Err(ProjectError::Prewarp {..})));
+
+        // Prewarping binds the sample rate, and binds are resolved after the parameters declared later
+        let project: Project = "[tf h]\nexpr = 1/(s*R + 1)\nmethod = bilinear\nprewarp = 1k\nbind = R\n[params]\nR = 1m\nrate = 48k".parse().unwrap();
+        assert_eq!(project.targets[0].bind, vec!["R"]);
+        let text = &project.generate().unwrap()[0].value.text;
+        assert!(!text.contains("rate") && !text.contains('R'));
+        assert!(matches!("[tf h]\nexpr = R\nbind = C\n[params]\nR = 1".parse::<Project>(), Err(ProjectError::Syntax {line: 3, ..})));
+        assert!(matches!("[defs]\ns = 2".parse::<Project>(), Err(ProjectError::Syntax {line: 2, ..})));
+        assert!(matches!("[defs]\nx = 2\nx = s".parse::<Project>(), Err(ProjectError::Syntax {line: 3, ..})));
+
+        assert!(matches!("[tf h]\nmethod = none".parse::<Project>(), Err(ProjectError::Syntax {line: 1, ..})));
+        assert!(matches!("[params]\nR = 1 [2..3]".parse::<Project>(), Err(ProjectError::Syntax {line: 2, ..})));
+        assert!(matches!("[params]\nR = 1 [3..0]".parse::<Project>(), Err(ProjectError::Syntax {line: 2, ..})));
+        let project: Project = "[params]\nG = -6 [-12..+12]".parse().unwrap();
+        assert_eq!(project.params[0].default, -6.0);
+        assert_eq!(project.params[0].range, Some((-12.0, 12.0)));
+
+        // An interrupted run leaves the existing outputs as they were
+        let dir = std::env::temp_dir().join(format!("transfer_function_project_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lp.txt");
+        std::fs::write(&path, "good\n").unwrap();
+        let mut project: Project = "[params]\nR = 1k\nC = 1u\n[tf lp]\nexpr = 1/(s*R*C + 1)^4\nmethod = bilinear\nbind = *".parse().unwrap();
+        project.targets[0].output = Some(path.clone());
+        let context = Context::new().with_budget(10);
+        assert!(matches!(context.run(|| project.run()), Err(Interrupt::BudgetExceeded {..})));
+        let context = Context::new();
+        context.cancel();
+        assert!(matches!(context.run(|| project.run()), Err(Interrupt::Cancelled)));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "good\n");
+        project.run().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("{}\n", project.generate().unwrap()[0].value.text));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn project_files()
+    {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("projects");
+        for entry in std::fs::read_dir(&dir).unwrap()
+        {
+            let path = entry.unwrap().path();
+            let project = Project::load(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            assert!(!project.targets.is_empty());
+        }
+
+        let project = Project::load(dir.join("first_order_all_pass.tfp")).unwrap();
+        let h = (Tf::s(1)*"tau" - 1)/(Tf::s(1)*"tau" + 1);
+        assert_eq!(project.generate().unwrap()[0].value.text, h.bilinear_transform().emit(Format::Rust));
+    }
 }
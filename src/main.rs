@@ -1,575 +1,313 @@
-#![feature(file_create_new)]
-#![feature(adt_const_params)]
-
-use core::ops::{Div, Mul};
-use std::fs::File;
-use std::io::Write;
-
-use array_math::ArrayOps;
-use num::traits::{Inv, Pow};
+use std::collections::HashMap;
+use std::error::Error;
+use std::f64::consts::TAU;
+use std::io::Read;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use transfer_function::compute::Compute;
+use transfer_function::context::Context;
+use transfer_function::emit::Format;
+use transfer_function::parallel::set_threads;
+use transfer_function::parse::{intern, parse_number};
+use transfer_function::project::Project;
+use transfer_function::sym::Sym;
 use transfer_function::{Tf, TfVar};
-use transfer_function::partial_one::PartialOne;
-use transfer_function::partial_zero::PartialZero;
-use transfer_function::polynomial::Polynomial;
-use transfer_function::coefficient::Coefficient;
 
-fn main() -> std::io::Result<()>
+const USAGE: &str = "\
+Usage: transfer_function <command> [options] [expression | -]
+
+Commands:
+    parse       Parse an expression in s and print it
+    simplify    Print the canonical form of an expression
+    eval        Bind symbols to numbers with --set and print the result
+    bilinear    Transform an expression in s to z
+    response    Print the magnitude in dB and phase in degrees over a frequency range
+    emit        Print the coefficients in a chosen format
+    project     Generate every target of a project file, given instead of the expression,
+                which sets the options --z, --prewarp, --format and --rate per target
+
+The expression is read from the arguments, from --file, or from stdin if it is `-` or missing.
+Numbers may have a sign and SI suffixes, like `-6`, `10k` or `4.7n`.
+
+Options:
+    --set NAME=VALUE    Bind a symbol to a number, may be repeated
+    --file PATH         Read the expression from a file
+    --rate NAME         The sample rate symbol of the bilinear transform [default: rate]
+    --prewarp HZ        Prewarp the bilinear transform to match at this frequency, needs the rate set
+    --z                 Apply the bilinear transform before `response` and `emit`
+    --from HZ           Start of the frequency range [default: 20]
+    --to HZ             End of the frequency range [default: 20k]
+    --points N          Number of logarithmically spaced frequencies [default: 32]
+    --format FORMAT     Output format of `emit`: text, rust or json [default: text]
+    --budget TERMS      Stop after forming this many term products
+    --progress          Report progress on stderr
+    --threads N         Number of threads [default: all]
+";
+
+struct Options
 {
-    third_order_filter()?;
-    //first_order_all_pass()?;
-    //bassman_tone_stack()?;
-    //equalizer()?;
-    //third_order_sallen_key()?;
-    //memory_man()?;
-    //pultec()?;
-
-    /*let h = [
-        Tf::s(1)*(Tf::from("C")*"R" + Tf::from("C2")*"R2")*"G"/(Tf::s(1)*"C"*"R" + 1)
-    ];
-
-    write_h(&h)?;*/
-    
-    //z_as_s(0, &["b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8"], &["a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7", "a8"])?;
-    //z_as_s(0, &["b0", "b1", "b2", "b3", "b4", "b5"], &["a0", "a1", "a2", "a3", "a4", "a5"])?;
-    //z_as_s(0, &["0", "b1", "b2", "b3", "b4", "b5"], &["a0", "a1", "a2", "a3", "a4", "a5"])?;
-
-    //z_as_s(0, &["b0", "b1", "b2", "b3"], &["a0", "a1", "a2", "a3"])?;
-
-    Ok(())
+    command: String,
+    expr: Option<String>,
+    file: Option<String>,
+    set: HashMap<&'static str, f64>,
+    rate: &'static str,
+    prewarp: Option<f64>,
+    z: bool,
+    from: f64,
+    to: f64,
+    points: usize,
+    format: Format,
+    budget: Option<usize>,
+    progress: bool
 }
 
-macro_rules! tf {
-    ($($t:tt)*) => {
-        Tf::from($($t)*)
-    };
-}
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
-fn third_order_filter() -> std::io::Result<()>
+fn main() -> ExitCode
 {
-    let s = Tf::s(1);
-    let s2 = s.clone()*s.clone();
-    let s3 = s2.clone()*s.clone();
-
-    let a_sos = s.clone() + "alpha";
-
-    let a = (s2.clone() + s.clone()*2*"zeta"*"omega" + tf!("omega")*"omega")*a_sos;
-
-    let h = [
-        tf!("k")*"k"*"k",
-        s*"k"*"k",
-        s2*"k",
-        s3
-    ].map(|b| (b/a.clone()).bilinear_transform());
-    //.chain([a_sos.inv().bilinear_transform()]);
-
-    write_h(&h)?;
-
-    Ok(())
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help")
+    {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS
+    }
+    match run(args)
+    {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
 
-fn equalizer() -> std::io::Result<()>
+fn number(s: &str) -> Result<f64>
 {
-    let s = Tf::s(1);
-
-    let z1 = [
-        tf!("R2"),
-        tf!(1)/"C2"/s.clone(),
-        tf!(1)/"C2"/s.clone()
-    ];
-    let c1 = [
-        tf!("C1"),
-        tf!("C1"),
-        tf!(0)
-    ];
-    let h = z1.comap(c1, |z1, c1| 
-        ((s.clone()*"RP"*c1.clone() + 1)*z1.clone() + s.clone()*(tf!(1) - "p")*"p"*"RP"*"RP"*c1.clone())
-        /((s.clone()*"RP"*c1.clone() + 1)*(z1.clone() + "R1") + tf!("RP")*(tf!(1) - "p") + s.clone()*(tf!(1) - "p")*"p"*"RP"*"RP"*c1.clone())
-    );
-
-    let hz = h.map(|h| h.bilinear_transform());
-
-    write_h(&hz)?;
-
-    Ok(())
+    parse_number(s)
+        .and_then(|c| c.compute(HashMap::new()))
+        .ok_or_else(|| format!("invalid number `{}`", s).into())
 }
 
-fn pultec() -> std::io::Result<()> {
-    let s = Tf::s(1);
-    let s2 = Tf::s(2);
-
-    let z1 = tf!("p_tc")*"R_TC" + "R_T" + tf!(1)/s.clone()/"c_tc";
-    let z2 = tf!("p_tc")*(tf!(1) - "p_tc")*"R_TC" + "R_T" + tf!(1)/s.clone()/"c_tc";
-    let z3 = tf!("p_tb")*"R_TB" + tf!(1)/s.clone()/"c_tb" + s.clone()*"l_tb" + "r_bw";
-    let z4 = tf!("p_tb")*(tf!(1) - "p_tb")*"R_TB" + tf!(1)/s.clone()/"c_tb" + s.clone()*"l_tb" + "r_bw";
-    let z5 = tf!(1)/(tf!(1)/"r_bc" + s.clone()*"c_bc") + "R_M";
-    let z6 = tf!(1)/(tf!(1)/"r_bb" + s.clone()*"c_bb");
-    let z7 = tf!(1)/s.clone()/"c_tb" + s.clone()*"l_tb" + "r_bw";
-
-    println!("computing...");
-
-    let h = [
-        (z2.clone()*"R_M"*"R_TC" + (z1.clone()*z5.clone() + z2.clone()*"R_TC")*z6.clone())
-            /((z1.clone()*z5.clone() + z2.clone()*"R_TC")*(z6.clone() + z4.clone()*"R_TB"/z3.clone()) + z2.clone()*z5.clone()*"R_TC"),
-        /*tf!("R_M")*(tf!("R_TC")*(z3.clone() + z6.clone()) + tf!("p_tb")*"R_TB"*z6.clone())
-            /(tf!("R_TB")*((z4.clone() + z6.clone()*(tf!(1) - "p_tb"))*"R_TC" + (z4.clone() + z6.clone())*"R_M" + z4.clone()*z6.clone()) + tf!("R_TC")*(tf!("R_M")*(z3.clone() + z6.clone()) + z6.clone()*z7.clone()))*/
-    ];
-
-    println!("writing...");
-    
-    //let h = h.map(|h| h.bilinear_transform());
-
-    write_h(&h)?;
-
-    /*for (n, b) in h.0.0.iter()
-        .enumerate()
+fn parse_args(mut args: Vec<String>) -> Result<Options>
+{
+    let command = args.remove(0);
+    if !["parse", "simplify", "eval", "bilinear", "response", "emit", "project"].contains(&command.as_str())
     {
-        let mut file = File::create(format!("h(s)_b{}.txt", n))?;
-    
-        write!(file, "{}", b)?;
+        return Err(format!("unknown command `{}`", command).into())
     }
-    for (n, a) in h.1.0.iter()
-        .enumerate()
-    {
-        let mut file = File::create(format!("h(s)_a{}.txt", n))?;
-    
-        write!(file, "{}", a)?;
-    }*/
-
-    /*println!("z transform...");
-
-    let mut file_b = File::create("b.txt")?;
-
-    let hnz = transfer_function::to_z(h_3);
-
-    println!("writing...");
-
-    writeln!(file_b, "[")?;
+    let mut options = Options {
+        command,
+        expr: None,
+        file: None,
+        set: HashMap::new(),
+        rate: "rate",
+        prewarp: None,
+        z: false,
+        from: 20.0,
+        to: 20e3,
+        points: 32,
+        format: Format::Text,
+        budget: None,
+        progress: false
+    };
+    set_threads(std::thread::available_parallelism().map_or(1, |n| n.get()));
 
-    for b in hnz.b.0
+    // The options given, to reject those that don't apply to the command
+    let mut given = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next()
     {
-        writeln!(file_b, "    {},", b)?;
+        if arg.starts_with("--")
+        {
+            given.push(arg.clone());
+        }
+        let mut value = || args.next().ok_or_else(|| format!("missing value for `{}`", arg));
+        match arg.as_str()
+        {
+            "--set" => {
+                let value = value()?;
+                let (name, x) = value.split_once('=')
+                    .ok_or_else(|| format!("expected NAME=VALUE, got `{}`", value))?;
+                options.set.insert(intern(name.trim()), number(x.trim())?);
+            },
+            "--file" | "-f" => options.file = Some(value()?),
+            "--rate" => options.rate = intern(&value()?),
+            "--prewarp" => options.prewarp = Some(number(&value()?)?),
+            "--z" => options.z = true,
+            "--from" => options.from = number(&value()?)?,
+            "--to" => options.to = number(&value()?)?,
+            "--points" => options.points = value()?.parse()?,
+            "--format" => options.format = value()?.parse()?,
+            "--budget" => options.budget = Some(value()?.parse()?),
+            "--progress" => options.progress = true,
+            "--threads" => set_threads(value()?.parse()?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option `{}`", arg).into()),
+            _ => {
+                if options.expr.is_some()
+                {
+                    return Err("more than one expression given".into())
+                }
+                options.expr = Some(arg)
+            }
+        }
     }
 
-    writeln!(file_b, "]")?;
-
-    let mut file_a = File::create("a.txt")?;
-
-    writeln!(file_a, "[")?;
-    
-    for a in hnz.a.0
+    // The options each command uses, besides --file, --budget, --progress and --threads
+    let used: &[&str] = match options.command.as_str()
+    {
+        "eval" | "project" => &["--set"],
+        "bilinear" => &["--set", "--rate", "--prewarp"],
+        "response" => &["--set", "--z", "--rate", "--prewarp", "--from", "--to", "--points"],
+        "emit" => &["--set", "--z", "--rate", "--prewarp", "--format"],
+        _ => &[]
+    };
+    let common = ["--file", "--budget", "--progress", "--threads"];
+    if let Some(arg) = given.iter().find(|arg| !common.contains(&arg.as_str()) && !used.contains(&arg.as_str()))
     {
-        writeln!(file_a, "    {},", a)?;
+        if options.command == "project" && ["--z", "--prewarp", "--format", "--rate"].contains(&arg.as_str())
+        {
+            return Err(format!("`{}` doesn't apply to `project`, set it in the project file instead", arg).into())
+        }
+        return Err(format!("`{}` doesn't apply to `{}`", arg, options.command).into())
     }
-
-    writeln!(file_a, "]")?;*/
-
-    println!("FINISHED!!!");
-
-    Ok(())
-}
-
-fn bassman_tone_stack() -> std::io::Result<()> {
-    let s = Tf::s(1);
-    let s2 = Tf::s(2);
-
-    let z1 = ((tf!("R1") + "R_T" + tf!(1)/s.clone()/"C1")*s.clone()*"C2" + 1)*(tf!(1)/s.clone()/"C3" + tf!("p_b")*"R_B" + (tf!(1) - "p_m")*"R_M") + "R1" + "R_T" + tf!(1)/s.clone()/"C1";
-    let z2 = tf!(1)/s.clone()/"C3" + tf!(1)/s.clone()/"C2" + tf!("p_b")*"R_B" + (tf!(1) - "p_m")*"R_M";
-    let z3 = tf!(1)/s.clone()/"C2" + tf!("p_b")*"R_B" + (tf!(1) - "p_m")*"R_M";
-    let z4 = s.clone()*"C2"*(tf!("R_T") + tf!(1)/s.clone()/"C1")*(tf!(1)/s.clone()/"C3" + tf!("p_b")*"R_B" + (tf!(1) - "p_m")*"R_M") + tf!("p_b")*"R_B" + (tf!(1) - "p_m")*"R_M" + "R_T" + tf!(1)/s.clone()/"C1";
-    let z5 = ((tf!("R1") + "R_T" + tf!(1)/s.clone()/"C1")*s.clone()*"C2" + 1)*(tf!("p_b")*"R_B" + (tf!(1) - "p_m")*"R_M") + "R_T" + tf!(1)/s.clone()/"C1";
-    let z6 = (tf!("p_b")*"R_B" + (tf!(1) - "p_m")*"R_M")/s.clone()/"C2"/z2.clone();
-
-    println!("computing...");
-
-    let h = [
-        (z1.clone()*(tf!("p_m")*"R_M" + z6.clone()) + (s.clone()*"C2"*"R1"*z2.clone() + tf!(1)/s.clone()/"C3")*(tf!("p_t")*"R_T" + z6.clone()))
-            /((tf!("R0") + tf!("p_m")*"R_M")*z1.clone() + z4.clone()*"R1" + z5.clone()/s.clone()/"C3")
-    ];
-
-    println!("writing...");
-    
-    //let h = h.map(|h| h.bilinear_transform());
-
-    write_h(&h)?;
-
-    println!("FINISHED!!!");
-
-    Ok(())
-}
-
-fn second_order_rc_filter() -> std::io::Result<()>
-{
-    let s = Tf::s(1);
-
-    let z1 = [
-        Tf::from("r1"),
-        s.clone().inv()/"c1"
-    ];
-    
-    let z2 = [
-        Tf::from("r2"),
-        s.clone().inv()/"c2"
-    ];
-
-    let h = [
-        (false, false),
-        (true, false),
-        (false, true),
-        (true, true)
-    ].map(|(s1, s2)| {
-        let z11 = z1[if s1 {1} else {0}].clone();
-        let z12 = z1[if !s1 {1} else {0}].clone();
-        let z21 = z2[if s2 {1} else {0}].clone();
-        let z22 = z2[if !s2 {1} else {0}].clone();
-        z22.clone()/z11.clone()/((z22.clone() + z21.clone())*(tf!(1)/z11.clone() + tf!(1)/z12.clone()) + tf!(1))
-    });
-
-    let h = h.map(|h| h.bilinear_transform());
-
-    write_h(&h)
-}
-
-fn second_order_rlc_filter() -> std::io::Result<()>
-{
-    let s = Tf::s(1);
-    
-    let z1 = [
-        Tf::from("l")*s.clone(),
-        tf!(0)
-    ];
-    
-    let z2 = [
-        Tf::from("r"),
-        s.clone().inv()/"c"
-    ];
-
-    let h = [
-        (false, false),
-        (true, false),
-        (false, true),
-        (true, true)
-    ].map(|(s1, s2)| {
-        let z11 = z1[if s1 {1} else {0}].clone();
-        let z12 = z1[if !s1 {1} else {0}].clone();
-        let z21 = z2[if s2 {1} else {0}].clone();
-        let z22 = z2[if !s2 {1} else {0}].clone();
-        (z12.clone() + z22.clone())/(z11.clone() + z12.clone() + z21.clone() + z22.clone())
-    });
-
-    let h = h.map(|h| h.bilinear_transform());
-
-    write_h(&h)
-}
-
-fn second_order_sallen_key() -> std::io::Result<()>
-{
-    let s = Tf::s(1);
-
-    let z1 = [
-        Tf::from("r1"),
-        s.clone().inv()/"c1"
-    ];
-    
-    let z2 = [
-        Tf::from("r2"),
-        s.clone().inv()/"c2"
-    ];
-
-    let g = Tf::from("g");
-
-    let h = [
-        (false, false),
-        (true, false),
-        (false, true),
-        (true, true)
-    ].map(|(s1, s2)| {
-        let z11 = z1[if s1 {1} else {0}].clone();
-        let z12 = z1[if !s1 {1} else {0}].clone();
-        let z21 = z2[if s2 {1} else {0}].clone();
-        let z22 = z2[if !s2 {1} else {0}].clone();
-        (g.clone()*z22.clone()*z12.clone())/(z12.clone()*(z22.clone() + z21.clone() + z11.clone()) + z11.clone()*(z22.clone()*(Tf::from(1)-g.clone()) + z21.clone()))
-    });
-
-    let h = h.map(|h| h.bilinear_transform());
-
-    write_h(&h)
+    if !options.z && options.command != "bilinear"
+    {
+        if let Some(arg) = given.iter().find(|arg| ["--rate", "--prewarp"].contains(&arg.as_str()))
+        {
+            return Err(format!("`{}` only applies with --z", arg).into())
+        }
+    }
+    Ok(options)
 }
 
-fn third_order_sallen_key() -> std::io::Result<()>
+fn read_expr(options: &Options) -> Result<Tf<{TfVar::S}>>
 {
-    let s = Tf::s(1);
-
-    let z1 = [
-        Tf::from("r1"),
-        s.clone().inv()/"c1"
-    ];
-    
-    let z2 = [
-        Tf::from("r2"),
-        s.clone().inv()/"c2"
-    ];
-    
-    let z3 = [
-        Tf::from("r3"),
-        s.clone().inv()/"c3"
-    ];
-
-    let g = Tf::from("g");
-
-    let h = [
-        (false, false, false),
-        (true, false, false),
-        (false, true, false),
-        (true, true, false),
-        (false, false, true),
-        (true, false, true),
-        (false, true, true),
-        (true, true, true)
-    ].map(|(s1, s2, s3)| {
-        let z11 = z1[if s1 {1} else {0}].clone();
-        let z12 = z1[if !s1 {1} else {0}].clone();
-        let z21 = z2[if s2 {1} else {0}].clone();
-        let z22 = z2[if !s2 {1} else {0}].clone();
-        let z31 = z3[if s3 {1} else {0}].clone();
-        let z32 = z3[if !s3 {1} else {0}].clone();
-        /*[
-            (z21.clone()/z12.clone() + z21.clone()/z11.clone() + 1),
-            (Tf::from(1)/z11.clone())/((Tf::from(1)/(g.clone()*z21.clone()))*(Tf::from(1) + z31.clone()/z32.clone())/(z21.clone()/z12.clone() + z21.clone()/z11.clone() + 1) + ((Tf::from(1)/(g.clone()*z21.clone()))*(Tf::from(1) + z31.clone()/z32.clone()) + (Tf::from(1)/z22.clone())*((Tf::from(1) + z31.clone()/z32.clone())/g.clone() - 1) + Tf::from(1)/(g.clone()*z32.clone())))
-        ]*/
-        (Tf::from(1)/z11.clone())/((Tf::from(1)/(g.clone()*z21.clone()))*(Tf::from(1) + z31.clone()/z32.clone()) + ((Tf::from(1)/(g.clone()*z21.clone()))*(Tf::from(1) + z31.clone()/z32.clone()) + (Tf::from(1)/z22.clone())*((Tf::from(1) + z31.clone()/z32.clone())/g.clone() - 1) + Tf::from(1)/(g.clone()*z32.clone()))*(z21.clone()/z12.clone() + z21.clone()/z11.clone() + 1))
-    });
-
-    /*let h_sos0 = h.clone().map(|[h, _]| h);
-    let h_o = h.map(|[_, h]| h);*/
-    //let h = h.map(|h| h.bilinear_transform());
-
-    write_h(&h)
+    let src = match (&options.expr, &options.file)
+    {
+        (Some(_), Some(_)) => return Err("both an expression and a file given".into()),
+        (Some(expr), None) if expr != "-" => expr.clone(),
+        (None, Some(file)) => std::fs::read_to_string(file)?,
+        _ => {
+            let mut src = String::new();
+            std::io::stdin().read_to_string(&mut src)?;
+            src
+        }
+    };
+    Ok(src.trim().parse()?)
 }
 
-fn memory_man() -> std::io::Result<()>
+fn run(args: Vec<String>) -> Result<()>
 {
-    let s = Tf::s(1);
-
-    // Delay input
-    /*let h = [
-        Tf::from(2)/((Tf::from(1) + Tf::from("R29")/"R32")*(Tf::from(1) + s.clone()*"R31"*"C37") + Tf::from("R29")*(s.clone()*(Tf::from("C37") - "C25") + s.clone()*s.clone()*"R31"*"C25"*"C37"))
-    ];*/
-
-    // Feedback mixer
-    /*let z1 = (s.clone()*"C7").inv() + "R13";
-    let z2 = (s.clone()*"C14").inv() + "R19";
-    let y1 = s.clone()*"C6" + Tf::from(1)/"R12";
-    let y2 = s.clone()*"C13" + z2.clone().inv();
-
-    let a = y1.clone()/2*(Tf::from(1) + (Tf::from(1) - "f")*"RF"*y2.clone() + z1.clone()*((Tf::from("f")*"RF").inv() + y2.clone())) + (Tf::from("f")*"RF").inv() + y2.clone();
-
-    let h = [
-        y1*(Tf::from(1) + (Tf::from(1) - "f")*"RF"*y2.clone() + z1.clone()*((Tf::from("f")*"RF").inv() + y2.clone()))/a.clone(),
-        z2.inv()/a
-    ];*/
-
-    // Input buffer
-    /*let h = [
-        -(s.clone()*"C3"*(Tf::from("rx") + "R11"))/(s.clone()*"C3"*"R8" + 1)/(s.clone()*"C5"*(Tf::from("rx") + "R11") + 1) 
-    ];*/
-
-    // Output buffer
-    let h = [
-        ((s.clone()*"R23"*"C18" + 1).inv()*(Tf::from("R21").inv() + Tf::from("R22").inv() + s.clone()*"C17" + ((s.clone()*"C19").inv() + "R24").inv()) - Tf::from("R21").inv())/(Tf::from("R22").inv() + s.clone()*"C17" + ((s.clone()*"C19").inv() + "R24").inv())
-    ];
-
-    // Output blend
-    /* x1 = Tf::from(1) + Tf::from("RB")*"p"*(Tf::from("R26").inv() + s.clone()*"C23");
-    let z1 = Tf::from("RB")*(Tf::from(1) - "p") + (s.clone()*"C20").inv();
-    let x2 = s.clone()*"C24"*x1.clone()*z1.clone() + (Tf::from(1) + s.clone()*"C24"*"R28")*(x1.clone() + z1.clone()*(Tf::from("R26").inv() + s.clone()*"C23"));
-    
-    let h = [
-        x1.clone()/x2.clone(),
-        (s.clone()*"C23"*z1.clone())/x2.clone()
-    ];*/
-
-    let h = h.map(|h| h.bilinear_transform());
-
-    let mut file = File::create("h.txt")?;
-
-    for (n, h) in h.into_iter()
-        .enumerate()
-        {
-        writeln!(file, "let b{} = [", n)?;
+    let options = parse_args(args)?;
+    if options.command == "project"
+    {
+        return project(&options)
+    }
+    let h = read_expr(&options)?;
 
-        for b in h.0.0
+    let output = context(&options).run(|| match options.command.as_str()
+    {
+        "parse" => Ok(h.to_string()),
+        "simplify" => Ok(h.canonical().to_string()),
+        "eval" => Ok(h.partial_eval(options.set.clone()).to_string()),
+        "bilinear" => Ok(bilinear(h, &options)?.z_inv().to_string()),
+        "response" => response(h, &options),
+        "emit" => if options.z
         {
-            writeln!(file, "    {},", b)?;
+            Ok(bilinear(h, &options)?.emit(options.format))
         }
-
-        writeln!(file, "];")?;
-
-        writeln!(file, "let a{} = [", n)?;
-        
-        for a in h.1.0
+        else
         {
-            writeln!(file, "    {},", a)?;
-        }
-
-        writeln!(file, "];")?;
-    }
-
+            Ok(h.partial_eval(options.set.clone()).emit(options.format))
+        },
+        _ => unreachable!()
+    })??;
+    println!("{}", output);
     Ok(())
 }
 
-fn first_order_all_pass() -> std::io::Result<()>
+fn context(options: &Options) -> Context
 {
-    let s = Tf::s(1);
-
-    let hs = (s.clone()*"tau" - 1)/(s.clone()*"tau" + 1);
-    
-    let hz = hs.bilinear_transform();
-
-    println!("let b = [");
-
-    for b in hz.0.0
+    let mut context = Context::new();
+    if let Some(budget) = options.budget
     {
-        println!("    {},", b);
+        context = context.with_budget(budget);
     }
-
-    println!("];");
-
-    println!("let a = [");
-    
-    for a in hz.1.0
+    if options.progress
     {
-        println!("    {},", a);
+        context = context.on_progress(Duration::from_secs(1), |p| eprintln!("{} terms, degree {}, {:?}", p.terms, p.degree, p.elapsed));
     }
-
-    println!("];");
-
-    Ok(())
+    context
 }
 
-fn phaser(n: usize) -> std::io::Result<()>
+/// Runs a project file, with the symbols set overriding the defaults of its parameters.
+fn project(options: &Options) -> Result<()>
 {
-    assert!(n >= 2);
-
-    let s = Tf::s(1);
-
-    fn pow(x: Tf<{TfVar::S}>, n: usize) -> Tf<{TfVar::S}>
+    let path = match (&options.expr, &options.file)
     {
-        let mut y = PartialOne::one();
-        for _ in 0..n
-        {
-            y = y*x.clone();
-        }
-        y
-    }
-
-    let hns = (s.clone()*"tau"*(Tf::from("f") + 1) - 1)*pow(s.clone()*"tau" + 1, n - 2)
-        /(pow(s.clone()*"tau" + 1, n - 1) + pow(s*"tau" - 1, n - 1)*"f");
-
-    let hnz = hns.bilinear_transform();
-
-    println!("let b{} = [", n);
-
-    for b in hnz.0.0
+        (Some(_), Some(_)) => return Err("both a project file and --file given".into()),
+        (Some(path), None) | (None, Some(path)) => path,
+        (None, None) => return Err("missing the project file".into())
+    };
+    let mut project = Project::load(path)?;
+    for (name, &value) in options.set.iter()
     {
-        println!("    {},", b);
+        project.set(name, value)?;
     }
 
-    println!("];");
-
-    println!("let a{} = [", n);
-    
-    for a in hnz.1.0
+    for output in context(options).run(|| project.run())??
     {
-        println!("    {},", a);
+        match &output.value.path
+        {
+            Some(path) => eprintln!("{} -> {} ({:?})", output.value.name, path.display(), output.elapsed),
+            None => println!("{}:\n{}", output.value.name, output.value.text)
+        }
     }
-
-    println!("];");
-
     Ok(())
 }
 
-fn write_h<const VAR: TfVar>(h: &[Tf<VAR>]) -> std::io::Result<()>
+/// The bilinear transform with the options applied, binding the symbols set.
+fn bilinear(mut h: Tf<{TfVar::S}>, options: &Options) -> Result<Tf<{TfVar::Z}>>
 {
-    let mut file = File::create("h.txt")?;
-
-    writeln!(file, "let b = [")?;
-
-    for (n, h) in h.iter()
-        .enumerate()
+    if let Some(f0) = options.prewarp
     {
-        writeln!(file, "    [")?;
-
-        for b in h.0.0.iter()
-        {
-            writeln!(file, "        {},", b)?;
-        }
-
-        writeln!(file, "    ],")?;
+        let rate = *options.set.get(options.rate)
+            .ok_or_else(|| format!("prewarping needs the sample rate, set it with --set {}=VALUE", options.rate))?;
+        h = h.prewarp(f0, rate)
+            .ok_or_else(|| format!("the prewarp frequency must be between 0 and half the sample rate, {} Hz", rate/2.0))?;
     }
-
-    writeln!(file, "];")?;
-    
-    writeln!(file, "let a = [")?;
-
-    for (n, h) in h.iter()
-        .enumerate()
+    let mut h = h.bilinear_transform();
+    if options.rate != "rate"
     {
-        writeln!(file, "    [")?;
-
-        for b in h.1.0.iter()
-        {
-            writeln!(file, "        {},", b)?;
-        }
-
-        writeln!(file, "    ],")?;
+        h = h.substitute(Sym("rate"), &Tf::from(options.rate));
     }
-    
-    writeln!(file, "];")?;
-
-    Ok(())
+    Ok(h.partial_eval(options.set.clone()))
 }
 
-fn z_as_s(k: usize, a: &[&'static str], b: &[&'static str]) -> std::io::Result<()>
+fn response(h: Tf<{TfVar::S}>, options: &Options) -> Result<String>
 {
-    let h: Tf<{TfVar::S}> = Tf(
-        Polynomial(
-            a.into_iter()
-                .map(|&a| Coefficient::from(a))
-                .collect()
-        ),
-        Polynomial(
-            b.into_iter()
-                .map(|&b| Coefficient::from(b))
-                .collect()
-        )
-    );
-    
-    println!("H{}(s) = {}", k, h);
-
-    println!("transforming...");
-
-    let h = h.bilinear_transform();
-
-    println!("writing...");
-
-    let mut file = File::create(format!("h{}(z)_b.txt", k))?;
-
-    for (n, b) in h.0.0.iter()
-        .enumerate()
+    if !(options.from > 0.0 && options.to >= options.from && options.points > 0)
     {
-        writeln!(file, "{},", b)?;
+        return Err("the frequency range must be positive and ascending".into())
     }
+    let z = if options.z {Some(bilinear(h.clone(), options)?)} else {None};
+    let rate = options.set.get(options.rate).copied();
 
-    let mut file = File::create(format!("h{}(z)_a.txt", k))?;
-    
-    for (n, a) in h.1.0.iter()
-        .enumerate()
+    let mut lines = vec!["f\tmagnitude_db\tphase_deg".to_string()];
+    for i in 0..options.points
     {
-    
-        writeln!(file, "{},", a)?;
+        let t = if options.points > 1 {i as f64/(options.points - 1) as f64} else {0.0};
+        let f = options.from*(options.to/options.from).powf(t);
+        let y = match &z
+        {
+            Some(z) => {
+                let rate = rate.ok_or_else(|| format!("the z-domain response needs the sample rate, set it with --set {}=VALUE", options.rate))?;
+                z.frequency_response(options.set.clone(), TAU*f/rate)
+            },
+            None => h.frequency_response(options.set.clone(), TAU*f)
+        }.ok_or("cannot evaluate the response, set all symbols with --set")?;
+        lines.push(format!("{}\t{}\t{}", f, 20.0*y.norm().log10(), y.arg().to_degrees()));
     }
-
-    println!("FINISHED!!!");
-
-    Ok(())
-}
\ No newline at end of file
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use num::Float;
+
+use crate::{compute::Compute, frequency_response::frequency_response, Tf, TfVar};
+
+/// The relative tolerance of a component value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance<F>
+where
+    F: Float
+{
+    /// Uniformly distributed within `±tol` of the nominal value.
+    Uniform(F),
+    /// Normally distributed, with a standard deviation of `sigma` times the nominal value.
+    Gaussian(F)
+}
+
+/// The spread of a quantity over all trials, per frequency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope<F>
+where
+    F: Float
+{
+    pub omega: Vec<F>,
+    /// Sorted samples of each frequency.
+    pub samples: Vec<Vec<F>>
+}
+
+impl<F> Envelope<F>
+where
+    F: Float
+{
+    pub fn min(&self) -> Vec<F>
+    {
+        self.percentile(F::zero())
+    }
+
+    pub fn max(&self) -> Vec<F>
+    {
+        self.percentile(F::from(100.0).unwrap())
+    }
+
+    /// The `p`-th percentile, linearly interpolated between samples.
+    pub fn percentile(&self, p: F) -> Vec<F>
+    {
+        self.samples.iter()
+            .map(|x| {
+                if x.is_empty()
+                {
+                    return F::nan()
+                }
+                let i = (p/F::from(100.0).unwrap()).max(F::zero()).min(F::one())*F::from(x.len() - 1).unwrap();
+                let i0 = i.floor().to_usize().unwrap();
+                let i1 = (i0 + 1).min(x.len() - 1);
+                let frac = i - F::from(i0).unwrap();
+                x[i0] + (x[i1] - x[i0])*frac
+            }).collect()
+    }
+}
+
+/// The result of a Monte Carlo tolerance analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonteCarlo<F>
+where
+    F: Float
+{
+    /// Magnitude of the response.
+    pub magnitude: Envelope<F>,
+    /// Phase of the response in radians, unwrapped around the phase of the nominal response.
+    pub phase: Envelope<F>
+}
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    /// Evaluates the frequency response for `trials` random draws of the component values, using a seeded generator.
+    ///
+    /// Symbols without a tolerance keep their nominal value.
+    /// Returns `None` if a symbol is missing from `nominal`.
+    pub fn monte_carlo<F>(
+        &self,
+        nominal: HashMap<&'static str, F>,
+        tolerances: &HashMap<&'static str, Tolerance<F>>,
+        omega: &[F],
+        trials: usize,
+        seed: u64
+    ) -> Option<MonteCarlo<F>>
+    where
+        F: Float
+    {
+        let (b, a) = self.compute(nominal.clone())?;
+        let h_nominal: Vec<_> = omega.iter()
+            .map(|&omega| frequency_response::<F, VAR>(&b, &a, omega))
+            .collect();
+
+        let mut rng = Rng(seed);
+        let mut magnitude = vec![Vec::with_capacity(trials); omega.len()];
+        let mut phase = vec![Vec::with_capacity(trials); omega.len()];
+
+        let mut tolerances: Vec<_> = tolerances.iter().collect();
+        tolerances.sort_by_key(|(&sym, _)| sym);
+
+        for _ in 0..trials
+        {
+            let mut syms = nominal.clone();
+            for (&sym, tolerance) in tolerances.iter()
+            {
+                let x = syms.get_mut(sym)?;
+                let dev = match tolerance
+                {
+                    Tolerance::Uniform(tol) => *tol*F::from(2.0*rng.next_f64() - 1.0).unwrap(),
+                    Tolerance::Gaussian(sigma) => *sigma*F::from(rng.next_gaussian()).unwrap()
+                };
+                *x = *x*(F::one() + dev);
+            }
+            let (b, a) = self.compute(syms)?;
+            for (i, (&omega, h0)) in omega.iter().zip(h_nominal.iter()).enumerate()
+            {
+                let h = frequency_response::<F, VAR>(&b, &a, omega);
+                magnitude[i].push(h.norm());
+                phase[i].push(h0.arg() + (h/h0).arg());
+            }
+        }
+
+        for x in magnitude.iter_mut().chain(phase.iter_mut())
+        {
+            x.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        }
+
+        Some(MonteCarlo {
+            magnitude: Envelope {omega: omega.to_vec(), samples: magnitude},
+            phase: Envelope {omega: omega.to_vec(), samples: phase}
+        })
+    }
+}
+
+/// SplitMix64, so that trials are reproducible from a seed.
+struct Rng(u64);
+
+impl Rng
+{
+    fn next_u64(&mut self) -> u64
+    {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64
+    {
+        (self.next_u64() >> 11) as f64/(1u64 << 53) as f64
+    }
+
+    fn next_gaussian(&mut self) -> f64
+    {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0*u1.ln()).sqrt()*(std::f64::consts::TAU*u2).cos()
+    }
+}
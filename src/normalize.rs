@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use num::{Float, Integer, One};
+
+use crate::{coefficient::Coefficient, compute::Compute, parse::intern, polynomial::Polynomial, simplify::Simplify, sym::Sym, weight::Weight, Tf, TfVar};
+
+/// The coefficients of a transfer function divided by its normalizing coefficient `den`.
+///
+/// A single term `den` is divided through exactly, and is then one.
+/// Otherwise the coefficients are multiplied by the shared reciprocal `inv`, which stands for `1/den`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalized<const VAR: TfVar>
+{
+    pub b: Polynomial<Coefficient>,
+    pub a: Polynomial<Coefficient>,
+    pub den: Coefficient,
+    pub inv: Option<Sym>
+}
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    /// Normalizes the canonical form so that `a[0]` is one.
+    ///
+    /// Returns `None` if `a[0]` is zero.
+    pub fn normalize_a0(&self) -> Option<Normalized<VAR>>
+    {
+        let h = self.canonical();
+        h.normalize_by(0)
+    }
+
+    /// Normalizes the canonical form so that the highest order coefficient of the denominator is one.
+    ///
+    /// Returns `None` if the denominator is zero.
+    pub fn normalize_monic(&self) -> Option<Normalized<VAR>>
+    {
+        let h = self.canonical();
+        let i = h.1.0.len().checked_sub(1)?;
+        h.normalize_by(i)
+    }
+
+    fn normalize_by(self, i: usize) -> Option<Normalized<VAR>>
+    {
+        let Tf(mut b, mut a) = self;
+        let den = a.0.get(i)?.clone();
+        if den.0.0.is_empty()
+        {
+            return None
+        }
+        if den.0.0.len() > 1
+        {
+            let used = |name: &str| b.0.iter().chain(a.0.iter())
+                .any(|c| c.0.0.keys().any(|e| e.0.keys().any(|s| s.0 == name)));
+            let name = (0..).map(|k| if k == 0 {"inv".to_string()} else {format!("inv{}", k)})
+                .find(|name| !used(name))
+                .unwrap();
+            let inv = Sym(intern(&name));
+            for c in b.0.iter_mut().chain(a.0.iter_mut())
+            {
+                *c = (c.clone()*Coefficient::from(inv.0)).trim();
+            }
+            a.0[i] = One::one();
+            return Some(Normalized {b, a, den, inv: Some(inv)})
+        }
+
+        for c in b.0.iter_mut().chain(a.0.iter_mut())
+        {
+            *c = (c.clone()/den.clone()).trim();
+        }
+        a.0[i] = One::one();
+        Some(Normalized {b, a, den: One::one(), inv: None})
+    }
+
+    /// A canonical form of the transfer function.
+    ///
+    /// Trailing zero coefficients are trimmed, common monomials and integer factors are removed,
+    /// and the first term of the lowest order nonzero coefficient of the denominator is positive.
+    pub fn canonical(&self) -> Self
+    {
+        let trim = |p: &Polynomial<Coefficient>| {
+            let mut p = Polynomial(p.0.iter().map(Coefficient::trim).collect::<Vec<_>>());
+            while p.0.last().is_some_and(|c| c.0.0.is_empty())
+            {
+                p.0.pop();
+            }
+            p
+        };
+
+        let mut h: Self = Tf(trim(&self.0), trim(&self.1));
+        h.simplify();
+        let mut h: Self = Tf(trim(&h.0), trim(&h.1));
+
+        let gcd = h.0.0.iter().chain(h.1.0.iter())
+            .flat_map(|c| c.0.0.values())
+            .try_fold(0, |gcd, w| match w
+            {
+                Weight::Int(w) => Some(gcd.gcd(w)),
+                Weight::Ratio(_) | Weight::Float(_) => None
+            });
+        let sign = h.1.0.iter()
+            .find_map(|a| a.0.0.values().next())
+            .is_some_and(|w| w.is_negative());
+        let factor = Weight::from(match (gcd, sign)
+        {
+            (Some(gcd), false) if gcd > 1 => gcd,
+            (Some(gcd), true) if gcd > 1 => -gcd,
+            (_, true) => -1,
+            _ => 1
+        });
+        if !factor.is_one()
+        {
+            for c in h.0.0.iter_mut().chain(h.1.0.iter_mut())
+            {
+                for w in c.0.0.values_mut()
+                {
+                    *w /= factor;
+                }
+            }
+        }
+        h
+    }
+
+    /// Checks whether the transfer functions are equal as rational functions, by cross-multiplication.
+    pub fn equivalent(&self, rhs: &Self) -> bool
+    {
+        (&self.0*&rhs.1 - &rhs.0*&self.1).0.iter()
+            .all(|c| c.trim().0.0.is_empty())
+    }
+}
+
+impl<F, const VAR: TfVar> Compute<F> for Normalized<VAR>
+where
+    F: Float
+{
+    type Output = (Polynomial<F>, Polynomial<F>);
+
+    fn compute(&self, mut syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        let den = self.den.compute(syms.clone())?;
+        if den.is_zero()
+        {
+            return None
+        }
+        if let Some(inv) = self.inv
+        {
+            syms.insert(inv.0, den.recip());
+            let b = self.b.compute(syms.clone())?;
+            let a = self.a.compute(syms)?;
+            return Some((b, a))
+        }
+        let b = self.b.compute(syms.clone())?;
+        let a = self.a.compute(syms)?;
+        Some((
+            Polynomial(b.0.into_iter().map(|b| b/den).collect()),
+            Polynomial(a.0.into_iter().map(|a| a/den).collect())
+        ))
+    }
+}
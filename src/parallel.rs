@@ -0,0 +1,90 @@
+use std::cell::Cell;
+use std::ops::Range;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::context;
+
+static THREADS: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    static WORKER: Cell<bool> = const {Cell::new(false)};
+    static SCOPED: Cell<Option<usize>> = const {Cell::new(None)};
+}
+
+/// Sets the number of threads used for polynomial and coefficient arithmetic.
+///
+/// The default of one is serial. Results are bit-identical for any number of threads.
+pub fn set_threads(threads: usize)
+{
+    THREADS.store(threads.max(1), Ordering::Relaxed)
+}
+
+/// The number of threads used for polynomial and coefficient arithmetic.
+pub fn threads() -> usize
+{
+    SCOPED.get().unwrap_or_else(|| THREADS.load(Ordering::Relaxed))
+}
+
+/// Runs `f` with `threads` threads on the current thread, instead of the number set with [set_threads].
+pub fn with_threads<R>(threads: usize, f: impl FnOnce() -> R) -> R
+{
+    struct Restore(Option<usize>);
+    impl Drop for Restore
+    {
+        fn drop(&mut self)
+        {
+            SCOPED.set(self.0)
+        }
+    }
+    let _restore = Restore(SCOPED.replace(Some(threads.max(1))));
+    f()
+}
+
+/// The number of threads [map_chunks](map_chunks) splits `0..len` across, one if it runs serially.
+pub(crate) fn chunk_threads(len: usize, min_chunk: usize) -> usize
+{
+    if WORKER.get() {1} else {threads()}
+        .min(len/min_chunk.max(1))
+        .max(1)
+}
+
+/// Applies `f` to consecutive chunks of `0..len` of at least `min_chunk` elements on scoped threads,
+/// returning the results in order.
+///
+/// Inside a worker thread everything runs serially, so that nested operations don't multiply the thread count.
+/// Workers report to the current [Context](crate::context::Context), so they all stop with it.
+pub(crate) fn map_chunks<R, F>(len: usize, min_chunk: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(Range<usize>) -> R + Sync
+{
+    let threads = chunk_threads(len, min_chunk);
+    if threads == 1
+    {
+        return vec![f(0..len)]
+    }
+
+    let chunk = len.div_ceil(threads);
+    let f = &f;
+    let context = context::current();
+    let results: Vec<_> = thread::scope(|s| {
+        let handles: Vec<_> = (0..len).step_by(chunk)
+            .map(|start| {
+                let context = context.clone();
+                s.spawn(move || {
+                    WORKER.set(true);
+                    context::enter(context);
+                    f(start..(start + chunk).min(len))
+                })
+            }).collect();
+        // All workers are joined before unwinding, so that a panic is passed on with its own payload
+        handles.into_iter()
+            .map(|h| h.join())
+            .collect()
+    });
+    results.into_iter()
+        .map(|y| y.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+        .collect()
+}
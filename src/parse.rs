@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use num::{traits::Inv, One};
+
+use crate::{coefficient::Coefficient, Int, Tf, TfVar};
+
+static NAMES: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+/// Gives a symbol name a static lifetime, leaking each distinct name once.
+pub fn intern(name: &str) -> &'static str
+{
+    let mut names = NAMES.lock().unwrap();
+    let names = names.get_or_insert_with(HashSet::new);
+    match names.get(name)
+    {
+        Some(&name) => name,
+        None => {
+            let name: &'static str = Box::leak(Box::from(name));
+            names.insert(name);
+            name
+        }
+    }
+}
+
+/// An error in an expression, at a byte offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError
+{
+    pub pos: usize,
+    pub message: String
+}
+
+impl Display for ParseError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{} at position {}", self.message, self.pos)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a number with an optional sign and SI suffix, like `10k`, `-6` or `4.7n`.
+///
+/// Integral values are exact, others are floating point.
+pub fn parse_number(s: &str) -> Option<Coefficient>
+{
+    let (neg, s) = match s.strip_prefix('-')
+    {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s))
+    };
+    let mut parser = Parser {src: s, pos: 0, defs: &HashMap::new()};
+    let y = parser.number().ok()?;
+    if parser.pos != s.len()
+    {
+        return None
+    }
+    Some(if neg {-y} else {y})
+}
+
+/// Parses an expression of numbers, symbols and `s`, with `+`, `-`, `*`, `/`, parentheses and integer powers `^`.
+impl FromStr for Tf<{TfVar::S}>
+{
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        parse_with(s, &HashMap::new())
+    }
+}
+
+/// Parses an expression like [from_str](Tf::from_str), with the names in `defs` standing for their transfer functions.
+pub fn parse_with(src: &str, defs: &HashMap<String, Tf<{TfVar::S}>>) -> Result<Tf<{TfVar::S}>, ParseError>
+{
+    let mut parser = Parser {src, pos: 0, defs};
+    let y = parser.expr()?;
+    parser.skip_ws();
+    if parser.pos != src.len()
+    {
+        return Err(parser.error("expected an operator"))
+    }
+    Ok(y)
+}
+
+struct Parser<'a>
+{
+    src: &'a str,
+    pos: usize,
+    defs: &'a HashMap<String, Tf<{TfVar::S}>>
+}
+
+impl<'a> Parser<'a>
+{
+    fn error(&self, message: &str) -> ParseError
+    {
+        ParseError {pos: self.pos, message: message.to_string()}
+    }
+
+    fn peek(&self) -> Option<char>
+    {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn skip_ws(&mut self)
+    {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace())
+        {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool
+    {
+        self.skip_ws();
+        if self.peek() == Some(c)
+        {
+            self.pos += c.len_utf8();
+            return true
+        }
+        false
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str
+    {
+        let start = self.pos;
+        while self.peek().is_some_and(&f)
+        {
+            self.pos += self.peek().unwrap().len_utf8();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn expr(&mut self) -> Result<Tf<{TfVar::S}>, ParseError>
+    {
+        let mut y = self.term()?;
+        loop
+        {
+            if self.eat('+')
+            {
+                y = y + self.term()?;
+            }
+            else if self.eat('-')
+            {
+                y = y - self.term()?;
+            }
+            else
+            {
+                return Ok(y)
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Tf<{TfVar::S}>, ParseError>
+    {
+        let mut y = self.unary()?;
+        loop
+        {
+            if self.eat('*')
+            {
+                y = y*self.unary()?;
+            }
+            else if self.eat('/')
+            {
+                y = y/self.unary()?;
+            }
+            else
+            {
+                return Ok(y)
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Tf<{TfVar::S}>, ParseError>
+    {
+        if self.eat('-')
+        {
+            return Ok(-self.unary()?)
+        }
+        let mut x = self.atom()?;
+        if !self.eat('^')
+        {
+            return Ok(x)
+        }
+
+        let neg = self.eat('-');
+        self.skip_ws();
+        let pos = self.pos;
+        let mut p: u32 = self.take_while(|c| c.is_ascii_digit())
+            .parse()
+            .map_err(|_| ParseError {pos, message: "expected an integer power".to_string()})?;
+        // Square and multiply
+        let mut y = Tf::one();
+        while p > 0
+        {
+            if p % 2 == 1
+            {
+                y = y*x.clone();
+            }
+            p /= 2;
+            if p > 0
+            {
+                x = x.clone()*x;
+            }
+        }
+        Ok(if neg {y.inv()} else {y})
+    }
+
+    fn atom(&mut self) -> Result<Tf<{TfVar::S}>, ParseError>
+    {
+        self.skip_ws();
+        match self.peek()
+        {
+            Some('(') => {
+                self.pos += 1;
+                let y = self.expr()?;
+                if !self.eat(')')
+                {
+                    return Err(self.error("expected `)`"))
+                }
+                Ok(y)
+            },
+            Some(c) if c.is_ascii_digit() || c == '.' => Ok(Tf::from(self.number()?)),
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                Ok(match self.defs.get(name)
+                {
+                    Some(h) => h.clone(),
+                    None if name == "s" => Tf::s(1),
+                    None => Tf::from(intern(name))
+                })
+            },
+            Some(_) => Err(self.error("expected a number, symbol or `(`")),
+            None => Err(self.error("unexpected end of expression"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Coefficient, ParseError>
+    {
+        let start = self.pos;
+        let int = self.take_while(|c| c.is_ascii_digit()).to_string();
+        let frac = if self.peek() == Some('.')
+        {
+            self.pos += 1;
+            self.take_while(|c| c.is_ascii_digit()).to_string()
+        }
+        else
+        {
+            String::new()
+        };
+        if int.is_empty() && frac.is_empty()
+        {
+            self.pos = start;
+            return Err(self.error("expected a number"))
+        }
+
+        let mut exp = 0;
+        if matches!(self.peek(), Some('e' | 'E')) && self.src[self.pos + 1..].starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+')
+        {
+            self.pos += 1;
+            let pos = self.pos;
+            let sign = if self.eat('-') {-1} else {self.eat('+'); 1};
+            exp = sign*self.take_while(|c| c.is_ascii_digit())
+                .parse::<i32>()
+                .map_err(|_| ParseError {pos, message: "expected an exponent".to_string()})?;
+        }
+        let suffix = match self.peek()
+        {
+            Some('T') => 12,
+            Some('G') => 9,
+            Some('M') => 6,
+            Some('k') => 3,
+            Some('m') => -3,
+            Some('u' | 'µ') => -6,
+            Some('n') => -9,
+            Some('p') => -12,
+            Some('f') => -15,
+            _ => 0
+        };
+        if suffix != 0
+        {
+            let c = self.peek().unwrap();
+            if !self.src[self.pos + c.len_utf8()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+            {
+                exp = exp.checked_add(suffix)
+                    .ok_or_else(|| self.error("exponent out of range"))?;
+                self.pos += c.len_utf8();
+            }
+        }
+        if self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_')
+        {
+            return Err(self.error("expected an operator after the number"))
+        }
+
+        // Exact if the value is an integer
+        let digits = format!("{}{}", int, frac);
+        let exp = i32::try_from(frac.len()).ok()
+            .and_then(|n| exp.checked_sub(n))
+            .ok_or_else(|| ParseError {pos: start, message: "exponent out of range".to_string()})?;
+        if exp >= 0
+        {
+            if let Some(y) = digits.parse::<Int>().ok()
+                .and_then(|d| d.checked_mul((10 as Int).checked_pow(exp as u32)?))
+            {
+                return Ok(Coefficient::from(y))
+            }
+        }
+        Ok(Coefficient::from(format!("{}e{}", digits, exp).parse::<f64>().unwrap()))
+    }
+}
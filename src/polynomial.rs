@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt::{Pointer, Display};
 use std::future::join;
 use std::hash::Hash;
@@ -8,9 +9,14 @@ use std::thread::{self, JoinHandle};
 use std::vec;
 
 use num::traits::{Inv, Pow};
-use num::{One, Zero, Float, Integer};
+use num::{One, Zero, Float, Integer, Complex};
 
 use crate::coefficient::Coefficient;
+use crate::compute::Compute;
+use crate::context;
+use crate::is_exact::IsExact;
+use crate::parallel;
+use crate::sym::Sym;
 use crate::partial_one::PartialOne;
 use crate::partial_zero::PartialZero;
 
@@ -28,7 +34,8 @@ impl<T> Polynomial<T>
 
     pub fn div_rem(&self, rhs: &Polynomial<T>) -> (Polynomial<T>, Result<(), Polynomial<T>>)
     where
-        T: Div<T, Output = T> + Mul<T, Output = T> + AddAssign + SubAssign + Neg<Output = T> + Sum + One + Clone + Zero + AddAssign
+        T: Div<T, Output = T> + Mul<T, Output = T> + AddAssign + SubAssign + Neg<Output = T> + Sum + One + Clone + Zero + AddAssign + IsExact + Send + Sync,
+        for<'a> &'a T: Mul<&'a T, Output = T>
     {
         let mut rem = self.clone();
         let mut div = PartialZero::zero();
@@ -62,6 +69,26 @@ fn test()
     println!("{:?}", c);
 }
 
+impl Polynomial<Coefficient>
+{
+    /// Differentiates each coefficient with respect to the symbol `sym`.
+    pub fn diff_sym(&self, sym: Sym) -> Polynomial<Coefficient>
+    {
+        Polynomial(self.0.iter().map(|b| b.diff(sym)).collect())
+    }
+}
+
+impl Polynomial<Coefficient>
+{
+    /// Binds the symbols in `syms` to numbers in each coefficient, keeping the remaining symbols.
+    pub fn partial_eval<F>(&self, syms: HashMap<&'static str, F>) -> Polynomial<Coefficient>
+    where
+        F: Float
+    {
+        Polynomial(self.0.iter().map(|b| b.partial_eval(syms.clone())).collect())
+    }
+}
+
 impl<T> From<T> for Polynomial<T>
 {
     fn from(value: T) -> Self
@@ -242,69 +269,123 @@ where
     }
 }
 
+/// Below this length of either factor, polynomials are multiplied with the schoolbook method.
+const KARATSUBA_THRESHOLD: usize = 32;
+/// The least number of coefficient products per thread.
+const PARALLEL_MIN_PRODUCTS: usize = 64;
+
 impl<T> Mul for &Polynomial<T>
 where
-    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T>
+    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T> + IsExact + Send + Sync,
+    for<'a> &'a T: Mul<&'a T, Output = T>
 {
     type Output = Polynomial<T>;
 
     fn mul(self, rhs: &Polynomial<T>) -> Self::Output
     {
-        let a = &self.0;
-        let b = &rhs.0;
-
-        let a_len = a.len();
-        let b_len = b.len();
-        let o = (a_len + b_len).saturating_sub(1);
-
-        Polynomial(
-            (0..o).map(|n| (n.saturating_sub(b_len - 1)..(n + 1).min(a_len))
-                    .map(|i| a[i].clone()*b[n - i].clone())
-                    .sum()
-                ).collect()
-            )
-        
-        /*fn mul<T>(p: &[T], q: &[T]) -> Polynomial<T>
-        where
-            T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T>
+        context::report_degree((self.0.len() + rhs.0.len()).saturating_sub(2));
+        Polynomial(mul::<T>(&self.0, &rhs.0))
+    }
+}
+
+/// Multiplies with Karatsuba's method above [KARATSUBA_THRESHOLD](KARATSUBA_THRESHOLD), and the schoolbook method below it.
+///
+/// Karatsuba's method cancels terms by subtraction, so it's only used if every coefficient [is exact](IsExact),
+/// to give the same result as the schoolbook method.
+fn mul<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Zero + Clone + AddAssign + SubAssign + Sum<T> + IsExact + Send + Sync,
+    for<'a> &'a T: Mul<&'a T, Output = T>
+{
+    if a.is_empty() || b.is_empty()
+    {
+        return vec![]
+    }
+    let (a, b) = if a.len() <= b.len() {(a, b)} else {(b, a)};
+    let len = a.len() + b.len() - 1;
+    if a.len() < KARATSUBA_THRESHOLD || !a.iter().chain(b.iter()).all(IsExact::is_exact)
+    {
+        return schoolbook::<T>(a, b)
+    }
+
+    let mut y = vec![T::zero(); len];
+    if 2*a.len() <= b.len()
+    {
+        // Unbalanced, so multiply by blocks of the shorter length
+        for (k, b) in b.chunks(a.len()).enumerate()
         {
-            match p.len()
+            for (i, x) in mul::<T>(a, b).into_iter().enumerate()
             {
-                0 => Polynomial(vec![]),
-                1 => {
-                    let p = p.into_iter()
-                        .next()
-                        .unwrap();
-
-                    Polynomial(q.into_iter()
-                        .map(|q| q.clone()*p.clone())
-                        .collect())
-                },
-                _ => {
-                    let l = q.len().max(p.len());
-                    let m = l.div_ceil(2);
-            
-                    let (a, b) = p.split_at(m.min(p.len()));
-                    let (c, d) = q.split_at(m.min(q.len()));
-
-                    let apb = Polynomial(a.to_vec()) + Polynomial(b.to_vec());
-                    let cpd = Polynomial(c.to_vec()) + Polynomial(d.to_vec());
-                    
-                    let amc = mul(a, c);
-                    let bmd = mul(b, d);
-
-                    amc.clone() + ((apb*cpd - amc - bmd.clone()) << m) + (bmd << (2*m))
-                }
+                y[k*a.len() + i] += x;
             }
         }
+        return y
+    }
+
+    let m = b.len().div_ceil(2);
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+    let sum = |x0: &[T], x1: &[T]| {
+        let mut x = x0.to_vec();
+        for (x, x1) in x.iter_mut().zip(x1.iter())
+        {
+            *x += x1.clone();
+        }
+        x
+    };
+
+    let z0 = mul::<T>(a0, b0);
+    let z2 = mul::<T>(a1, b1);
+    let mut z1 = mul::<T>(&sum(a0, a1), &sum(b0, b1));
+    z1.resize(z1.len().max(z0.len()).max(z2.len()), T::zero());
+    for (z1, z0) in z1.iter_mut().zip(z0.iter())
+    {
+        *z1 -= z0.clone();
+    }
+    for (z1, z2) in z1.iter_mut().zip(z2.iter())
+    {
+        *z1 -= z2.clone();
+    }
 
-        mul(a, b)*/
+    y.resize(len.max(m + z1.len()).max(2*m + z2.len()), T::zero());
+    for (i, z0) in z0.into_iter().enumerate()
+    {
+        y[i] += z0;
+    }
+    for (i, z1) in z1.into_iter().enumerate()
+    {
+        y[m + i] += z1;
     }
+    for (i, z2) in z2.into_iter().enumerate()
+    {
+        y[2*m + i] += z2;
+    }
+    // The excess terms cancel
+    y.truncate(len);
+    y
+}
+
+fn schoolbook<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Sum<T> + Send + Sync,
+    for<'a> &'a T: Mul<&'a T, Output = T>
+{
+    // Each output coefficient is accumulated in the same order regardless of the number of threads
+    let len = (a.len() + b.len()).saturating_sub(1);
+    parallel::map_chunks(len, PARALLEL_MIN_PRODUCTS.div_ceil(a.len().min(b.len()).max(1)), |n| n
+        .map(|n| (n.saturating_sub(b.len() - 1)..(n + 1).min(a.len()))
+            .map(|i| &a[i]*&b[n - i])
+            .sum()
+        ).collect::<Vec<_>>()
+    ).into_iter()
+        .flatten()
+        .collect()
 }
 
 impl<T> Mul for Polynomial<T>
 where
-    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T>
+    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T> + IsExact + Send + Sync,
+    for<'a> &'a T: Mul<&'a T, Output = T>
 {
     type Output = Self;
 
@@ -315,7 +396,8 @@ where
 }
 impl<T> MulAssign for Polynomial<T>
 where
-    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T>
+    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T> + IsExact + Send + Sync,
+    for<'a> &'a T: Mul<&'a T, Output = T>
 {
     fn mul_assign(&mut self, rhs: Self)
     {
@@ -326,7 +408,8 @@ where
 
 impl<T> Div for &Polynomial<T>
 where
-    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T> + Div<T, Output = T>
+    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T> + Div<T, Output = T> + IsExact + Send + Sync,
+    for<'a> &'a T: Mul<&'a T, Output = T>
 {
     type Output = (Polynomial<T>, Result<(), Polynomial<T>>);
 
@@ -338,7 +421,8 @@ where
 
 impl<T> Div for Polynomial<T>
 where
-    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T> + Div<T, Output = T>
+    T: One + Zero + Clone + AddAssign + SubAssign + Neg<Output = T> + Sum<T> + Div<T, Output = T> + IsExact + Send + Sync,
+    for<'a> &'a T: Mul<&'a T, Output = T>
 {
     type Output = (Polynomial<T>, Result<(), Polynomial<T>>);
 
@@ -464,4 +548,183 @@ impl<T> Polynomial<T>
         }
         Ok(())
     }
+}
+
+impl<T, F> Compute<F> for Polynomial<T>
+where
+    T: Compute<F, Output = F>,
+    F: Float
+{
+    type Output = Polynomial<F>;
+
+    fn compute(&self, syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        Some(Polynomial(
+            self.0.iter()
+                .map(|b| b.compute(syms.clone()))
+                .collect::<Option<Vec<F>>>()?
+        ))
+    }
+}
+
+impl<T> Polynomial<T>
+{
+    /// Evaluates the polynomial at `x` using Horner's method.
+    pub fn horner<X>(&self, x: X) -> X
+    where
+        T: Clone,
+        X: Clone + Zero + Mul<Output = X> + Add<T, Output = X>
+    {
+        let mut y = X::zero();
+        for b in self.0.iter().rev()
+        {
+            y = y*x.clone() + b.clone();
+        }
+        y
+    }
+}
+
+impl<F> Polynomial<F>
+where
+    F: Float
+{
+    /// Finds all complex roots of the polynomial using the Aberth-Ehrlich method.
+    ///
+    /// Roots at the origin are split off exactly before iterating, and zero leading coefficients are ignored.
+    pub fn roots(&self) -> Vec<Complex<F>>
+    {
+        let mut c: Vec<Complex<F>> = self.0.iter()
+            .map(|&b| Complex::from(b))
+            .collect();
+        while c.last().is_some_and(Zero::is_zero)
+        {
+            c.pop();
+        }
+
+        let mut roots = vec![];
+        while c.len() > 1 && Zero::is_zero(&c[0])
+        {
+            c.remove(0);
+            roots.push(Zero::zero());
+        }
+
+        let n = c.len().saturating_sub(1);
+        if n == 0
+        {
+            return roots
+        }
+        let lead = c[n];
+        for b in c.iter_mut()
+        {
+            *b = *b/lead;
+        }
+        let c = Polynomial(c);
+
+        let radius = F::one() + c.0[..n].iter()
+            .map(|b| b.norm())
+            .fold(F::zero(), F::max);
+        let tau = F::from(std::f64::consts::TAU).unwrap();
+        let mut z: Vec<Complex<F>> = (0..n).map(|k| Complex::from_polar(
+                radius,
+                tau*F::from(k).unwrap()/F::from(n).unwrap() + F::from(0.4).unwrap()
+            ))
+            .collect();
+
+        // Aberth-Ehrlich iteration, freezing each root once its residual is at rounding level
+        let abs = Polynomial(c.0.iter().map(|b| b.norm()).collect::<Vec<F>>());
+        let dc = derivative(&c);
+        let mut converged = vec![false; n];
+        for _ in 0..1000
+        {
+            for i in 0..n
+            {
+                if converged[i]
+                {
+                    continue
+                }
+                let p = c.horner(z[i]);
+                if p.norm() <= F::from(4.0).unwrap()*F::epsilon()*abs.horner(z[i].norm())
+                {
+                    converged[i] = true;
+                    continue
+                }
+                let ratio = p/dc.horner(z[i]);
+                let repulsion = (0..n).filter(|&j| j != i)
+                    .map(|j| (z[i] - z[j]).inv())
+                    .fold(Complex::from(F::zero()), |s, r| s + r);
+                let dz = ratio/(Complex::from(F::one()) - ratio*repulsion);
+                if !(dz.re.is_finite() && dz.im.is_finite())
+                {
+                    converged[i] = true;
+                    continue
+                }
+                z[i] = z[i] - dz;
+            }
+            if converged.iter().all(|&c| c)
+            {
+                break
+            }
+        }
+
+        // A cluster of m roots around a multiple root is refined as a simple root of the (m - 1)-th derivative
+        let cluster_tol = F::epsilon().sqrt().sqrt();
+        let mut done = vec![false; n];
+        for i in 0..n
+        {
+            if done[i]
+            {
+                continue
+            }
+            let cluster: Vec<usize> = (i..n).filter(|&j| !done[j] && (z[j] - z[i]).norm() <= cluster_tol*z[i].norm().max(F::one()))
+                .collect();
+            if cluster.len() > 1
+            {
+                let mut d = c.clone();
+                for _ in 1..cluster.len()
+                {
+                    d = derivative(&d);
+                }
+                let dd = derivative(&d);
+                let mut r = cluster.iter()
+                    .map(|&j| z[j])
+                    .fold(Complex::from(F::zero()), |s, z| s + z)/F::from(cluster.len()).unwrap();
+                for _ in 0..16
+                {
+                    let dr = d.horner(r)/dd.horner(r);
+                    if !(dr.re.is_finite() && dr.im.is_finite())
+                    {
+                        break
+                    }
+                    r = r - dr;
+                    if dr.norm() <= F::epsilon()*r.norm()
+                    {
+                        break
+                    }
+                }
+                if c.horner(r).norm() <= F::from(16.0).unwrap()*F::epsilon()*abs.horner(r.norm())
+                {
+                    for &j in cluster.iter()
+                    {
+                        z[j] = r;
+                        done[j] = true;
+                    }
+                }
+            }
+            done[i] = true;
+        }
+
+        roots.append(&mut z);
+        roots
+    }
+}
+
+fn derivative<F>(p: &Polynomial<Complex<F>>) -> Polynomial<Complex<F>>
+where
+    F: Float
+{
+    Polynomial(p.0.iter()
+        .enumerate()
+        .skip(1)
+        .map(|(k, &b)| b*F::from(k).unwrap())
+        .collect())
 }
\ No newline at end of file
@@ -1,8 +1,8 @@
 use std::{collections::{HashMap, BTreeMap}, ops::{MulAssign, AddAssign, Mul, Div, DivAssign, SubAssign, Neg}, hash::Hash};
 
-use num::{One, traits::Inv};
+use num::{One, traits::Inv, Float, ToPrimitive};
 
-use crate::{partial_one::PartialOne, partial_zero::PartialZero, simplify::Simplify};
+use crate::{partial_one::PartialOne, partial_zero::PartialZero, simplify::Simplify, compute::Compute};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct PoweredProduct<E, P>(pub BTreeMap<E, P>)
@@ -248,4 +248,23 @@ where
             }
         }
     }
+}
+
+impl<E, P, F> Compute<F> for PoweredProduct<E, P>
+where
+    E: Compute<F, Output = F> + Eq + Hash + Ord,
+    P: ToPrimitive,
+    F: Float
+{
+    type Output = F;
+
+    fn compute(&self, syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        let mut y = F::one();
+        for (e, p) in self.0.iter()
+        {
+            y = y*e.compute(syms.clone())?.powi(p.to_i32()?);
+        }
+        Some(y)
+    }
 }
\ No newline at end of file
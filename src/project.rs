@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{batch::{batch, Timed}, compute::Compute, context::{self, Interrupt}, emit::Format, parse::{intern, parse_number, parse_with}, Tf, TfVar};
+
+/// A parameter symbol, with its default value and the range it may be set to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param
+{
+    pub name: &'static str,
+    pub default: f64,
+    pub range: Option<(f64, f64)>
+}
+
+/// How a transfer function in s is discretized.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Method
+{
+    /// Kept in s.
+    #[default]
+    None,
+    /// The [bilinear transform](Tf::bilinear_transform), optionally [prewarped](Tf::prewarp) to a frequency in Hz.
+    Bilinear {prewarp: Option<f64>}
+}
+
+/// A named transfer function, and how to generate its output.
+#[derive(Debug, Clone)]
+pub struct Target
+{
+    pub name: String,
+    pub tf: Tf<{TfVar::S}>,
+    pub method: Method,
+    /// The parameters bound to their values, the others are kept as symbols.
+    ///
+    /// A prewarped target also binds `rate`, since the prewarp is computed for its value.
+    pub bind: Vec<&'static str>,
+    pub format: Format,
+    /// The file to write to, or `None` for standard output.
+    pub output: Option<PathBuf>
+}
+
+/// The generated output of a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output
+{
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub text: String
+}
+
+/// A project file, describing parameters and transfer functions to generate coefficients for.
+///
+/// The file consists of sections, with `#` starting a comment:
+///
+/// ```text
+/// [params]
+/// R = 10k [1k..100k]   # name = default [min..max], the range is optional
+/// G = -6 [-12..12]
+/// C = 4.7n
+/// rate = 48k           # the sample rate, needed to prewarp
+///
+/// [defs]
+/// z = R + 1/(s*C)      # subexpressions, usable in later defs and targets, and defined once
+///
+/// [tf lowpass]
+/// expr = 1/(s*C*z)
+/// method = bilinear    # none or bilinear [default: none]
+/// prewarp = 1k         # match the response at this frequency, which binds `rate`
+/// bind = R, C          # parameters to bind to their values, or * for all [default: none]
+/// format = rust        # text, rust or json [default: text]
+/// output = lowpass.rs  # relative to the project file [default: standard output]
+/// ```
+///
+/// The `projects` directory of the repository has a project file for each of the circuits modelled with this crate.
+#[derive(Debug, Clone, Default)]
+pub struct Project
+{
+    pub params: Vec<Param>,
+    pub targets: Vec<Target>
+}
+
+/// An error in a project file, or in running it.
+#[derive(Debug)]
+pub enum ProjectError
+{
+    Syntax {line: usize, message: String},
+    UnknownParam(String),
+    OutOfRange {name: &'static str, value: f64, range: (f64, f64)},
+    /// A target is prewarped, but there's no `rate` parameter.
+    NoRate(String),
+    /// A target is prewarped to a frequency outside of `0..rate/2`.
+    Prewarp {name: String, f0: f64, rate: f64},
+    /// The [context](crate::context::Context) it was run in was stopped, so the outputs are incomplete.
+    Interrupt(Interrupt),
+    Io(std::io::Error)
+}
+
+impl Display for ProjectError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            ProjectError::Syntax {line, message} => write!(f, "line {}: {}", line, message),
+            ProjectError::UnknownParam(name) => write!(f, "unknown parameter `{}`", name),
+            ProjectError::OutOfRange {name, value, range: (min, max)} => write!(f, "{} = {} is outside of {}..{}", name, value, min, max),
+            ProjectError::NoRate(name) => write!(f, "`{}` is prewarped, but there's no `rate` parameter", name),
+            ProjectError::Prewarp {name, f0, rate} => write!(f, "`{}` is prewarped to {} Hz, outside of 0..{} Hz", name, f0, rate/2.0),
+            ProjectError::Interrupt(interrupt) => write!(f, "{}", interrupt),
+            ProjectError::Io(e) => write!(f, "{}", e)
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl From<std::io::Error> for ProjectError
+{
+    fn from(e: std::io::Error) -> Self
+    {
+        ProjectError::Io(e)
+    }
+}
+
+enum Section
+{
+    None,
+    Params,
+    Defs,
+    Tf
+}
+
+fn number(s: &str) -> Option<f64>
+{
+    parse_number(s)?.compute(HashMap::new())
+}
+
+impl FromStr for Project
+{
+    type Err = ProjectError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err>
+    {
+        let mut project = Project::default();
+        let mut defs = HashMap::new();
+        let mut section = Section::None;
+        // The line of the current target's header, and whether its expression is set
+        let mut header = 0;
+        let mut has_expr = false;
+        // The `bind` of each target and its line, resolved once all parameters are known
+        let mut binds = vec![];
+
+        for (i, line) in src.lines().enumerate()
+        {
+            let error = |message: String| ProjectError::Syntax {line: i + 1, message};
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty()
+            {
+                continue
+            }
+
+            if let Some(header_line) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+            {
+                if !has_expr && matches!(section, Section::Tf)
+                {
+                    return Err(ProjectError::Syntax {line: header, message: "missing `expr`".to_string()})
+                }
+                section = match header_line.split_whitespace().collect::<Vec<_>>()[..]
+                {
+                    ["params"] => Section::Params,
+                    ["defs"] => Section::Defs,
+                    ["tf", name] => {
+                        if project.targets.iter().any(|t| t.name == name)
+                        {
+                            return Err(error(format!("`{}` is defined twice", name)))
+                        }
+                        project.targets.push(Target {
+                            name: name.to_string(),
+                            tf: Tf::from(0),
+                            method: Method::None,
+                            bind: vec![],
+                            format: Format::Text,
+                            output: None
+                        });
+                        header = i + 1;
+                        has_expr = false;
+                        Section::Tf
+                    },
+                    _ => return Err(error(format!("unknown section `{}`", line)))
+                };
+                continue
+            }
+
+            let (key, value) = line.split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| error("expected `key = value`".to_string()))?;
+            match section
+            {
+                Section::None => return Err(error("expected a section".to_string())),
+                Section::Params => {
+                    if project.param(key).is_some()
+                    {
+                        return Err(error(format!("`{}` is defined twice", key)))
+                    }
+                    let (default, range) = match value.split_once('[')
+                    {
+                        Some((default, range)) => {
+                            let (min, max) = range.strip_suffix(']')
+                                .and_then(|range| range.split_once(".."))
+                                .and_then(|(min, max)| Some((number(min.trim())?, number(max.trim())?)))
+                                .ok_or_else(|| error(format!("invalid range `[{}`", range)))?;
+                            if min > max
+                            {
+                                return Err(error(format!("the range `[{}` is descending", range)))
+                            }
+                            (default.trim(), Some((min, max)))
+                        },
+                        None => (value, None)
+                    };
+                    let param = Param {
+                        name: intern(key),
+                        default: number(default).ok_or_else(|| error(format!("invalid number `{}`", default)))?,
+                        range
+                    };
+                    param.check(param.default).map_err(|e| error(e.to_string()))?;
+                    project.params.push(param);
+                },
+                Section::Defs => {
+                    if key == "s"
+                    {
+                        return Err(error("`s` can't be redefined".to_string()))
+                    }
+                    if defs.contains_key(key)
+                    {
+                        return Err(error(format!("`{}` is defined twice", key)))
+                    }
+                    let h = parse_with(value, &defs).map_err(|e| error(e.to_string()))?;
+                    defs.insert(key.to_string(), h);
+                },
+                Section::Tf => {
+                    let target = project.targets.last_mut().unwrap();
+                    match key
+                    {
+                        "expr" => {
+                            target.tf = parse_with(value, &defs).map_err(|e| error(e.to_string()))?;
+                            has_expr = true;
+                        },
+                        "method" => target.method = match value
+                        {
+                            "none" => Method::None,
+                            "bilinear" => Method::Bilinear {prewarp: None},
+                            _ => return Err(error(format!("unknown method `{}`", value)))
+                        },
+                        "prewarp" => match &mut target.method
+                        {
+                            Method::Bilinear {prewarp} => *prewarp = Some(number(value).ok_or_else(|| error(format!("invalid number `{}`", value)))?),
+                            Method::None => return Err(error("`prewarp` needs `method = bilinear` first".to_string()))
+                        },
+                        "bind" => binds.push((project.targets.len() - 1, i + 1, value)),
+                        "format" => target.format = value.parse().map_err(error)?,
+                        "output" => target.output = Some(PathBuf::from(value)),
+                        _ => return Err(error(format!("unknown key `{}`", key)))
+                    }
+                }
+            }
+        }
+        if !has_expr && matches!(section, Section::Tf)
+        {
+            return Err(ProjectError::Syntax {line: header, message: "missing `expr`".to_string()})
+        }
+
+        for (i, line, value) in binds
+        {
+            project.targets[i].bind = if value == "*"
+            {
+                project.params.iter().map(|p| p.name).collect()
+            }
+            else
+            {
+                value.split(',')
+                    .map(|name| name.trim())
+                    .filter(|name| !name.is_empty())
+                    .map(|name| project.param(name)
+                        .map(|p| p.name)
+                        .ok_or_else(|| ProjectError::Syntax {line, message: format!("unknown parameter `{}`", name)})
+                    ).collect::<Result<_, _>>()?
+            };
+        }
+        Ok(project)
+    }
+}
+
+impl Param
+{
+    fn check(&self, value: f64) -> Result<(), ProjectError>
+    {
+        match self.range
+        {
+            Some((min, max)) if !(min..=max).contains(&value) => Err(ProjectError::OutOfRange {name: self.name, value, range: (min, max)}),
+            _ => Ok(())
+        }
+    }
+}
+
+impl Project
+{
+    /// Reads a project file, with the outputs relative to its directory.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProjectError>
+    {
+        let path = path.as_ref();
+        let mut project: Self = std::fs::read_to_string(path)?.parse()?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for target in project.targets.iter_mut()
+        {
+            target.output = target.output.take().map(|output| dir.join(output));
+        }
+        Ok(project)
+    }
+
+    pub fn param(&self, name: &str) -> Option<&Param>
+    {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    /// Overrides the default value of a parameter, which must be within its range.
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), ProjectError>
+    {
+        let param = self.params.iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| ProjectError::UnknownParam(name.to_string()))?;
+        param.check(value)?;
+        param.default = value;
+        Ok(())
+    }
+
+    /// Builds the output of every target in a [batch](batch), without writing it.
+    ///
+    /// Returns [ProjectError::Interrupt] if the [context](crate::context::Context) it is run in was stopped.
+    pub fn generate(&self) -> Result<Vec<Timed<Output>>, ProjectError>
+    {
+        let rate = self.param("rate").map(|p| p.default);
+        for target in self.targets.iter()
+        {
+            if let Method::Bilinear {prewarp: Some(f0)} = target.method
+            {
+                let rate = rate.ok_or_else(|| ProjectError::NoRate(target.name.clone()))?;
+                if !(f0 > 0.0 && f0 < rate/2.0)
+                {
+                    return Err(ProjectError::Prewarp {name: target.name.clone(), f0, rate})
+                }
+            }
+        }
+
+        let outputs = batch(&self.targets, |target| {
+            let mut values: HashMap<&'static str, f64> = target.bind.iter()
+                .map(|&name| (name, self.param(name).unwrap().default))
+                .collect();
+            let text = match target.method
+            {
+                Method::None => target.tf.partial_eval(values).emit(target.format),
+                Method::Bilinear {prewarp} => {
+                    let h = match prewarp
+                    {
+                        Some(f0) => {
+                            let rate = rate.unwrap();
+                            values.insert("rate", rate);
+                            target.tf.prewarp(f0, rate).unwrap()
+                        },
+                        None => target.tf.clone()
+                    };
+                    h.bilinear_transform().partial_eval(values).emit(target.format)
+                }
+            };
+            Output {name: target.name.clone(), path: target.output.clone(), text}
+        });
+        match context::stopped()
+        {
+            Some(interrupt) => Err(ProjectError::Interrupt(interrupt)),
+            None => Ok(outputs)
+        }
+    }
+
+    /// Builds the output of every target, and writes those with a path to their files.
+    ///
+    /// Nothing is written if the outputs are incomplete, and each file is replaced by renaming a temporary one,
+    /// so that an existing output is never left half written.
+    pub fn run(&self) -> Result<Vec<Timed<Output>>, ProjectError>
+    {
+        let outputs = self.generate()?;
+        for output in outputs.iter()
+        {
+            if let Some(path) = &output.value.path
+            {
+                let mut tmp = path.clone().into_os_string();
+                tmp.push(".tmp");
+                std::fs::write(&tmp, format!("{}\n", output.value.text))?;
+                std::fs::rename(&tmp, path)?;
+            }
+        }
+        Ok(outputs)
+    }
+}
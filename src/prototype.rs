@@ -0,0 +1,251 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use num::{Complex, One};
+
+use crate::{coefficient::Coefficient, polynomial::Polynomial, simplify::Simplify, Tf, TfVar};
+
+/// Zeros, poles and gain.
+pub type Zpk = (Vec<Complex<f64>>, Vec<Complex<f64>>, f64);
+
+/// A family of analog lowpass prototypes.
+///
+/// Ripple and attenuation are in dB. They enter the poles transcendentally, so they must be numeric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prototype
+{
+    /// Maximally flat magnitude, -3 dB at the cutoff.
+    Butterworth,
+    /// Equiripple passband with `ripple`, ending at the cutoff.
+    ChebyshevI {ripple: f64},
+    /// Equiripple stopband with `attenuation`, starting at the cutoff.
+    ChebyshevII {attenuation: f64},
+    /// Maximally flat group delay, with the same high-frequency asymptote as the Butterworth filter.
+    Bessel,
+    /// Equiripple passband with `ripple` ending at the cutoff, and equiripple stopband with `attenuation`.
+    Elliptic {ripple: f64, attenuation: f64}
+}
+
+impl Prototype
+{
+    /// The zeros, poles and gain of the prototype with a cutoff of one radian per second.
+    ///
+    /// Returns `None` if the order is zero or the ripple parameters are invalid.
+    pub fn zpk(&self, order: usize) -> Option<Zpk>
+    {
+        if order == 0
+        {
+            return None
+        }
+        let n = order as f64;
+        let m = || (0..order).map(|i| (2*i + 1) as f64 - n);
+        let j = Complex::i();
+
+        let (z, p, k) = match *self
+        {
+            Prototype::Butterworth => {
+                let p: Vec<_> = m().map(|m| -(j*PI*m/(2.0*n)).exp()).collect();
+                (vec![], p, 1.0)
+            },
+            Prototype::ChebyshevI {ripple} => {
+                if ripple.is_nan() || ripple <= 0.0
+                {
+                    return None
+                }
+                let eps = (10f64.powf(ripple/10.0) - 1.0).sqrt();
+                let mu = (1.0/eps).asinh()/n;
+                let p: Vec<_> = m().map(|m| -(j*PI*m/(2.0*n) + mu).sinh()).collect();
+                let mut k = p.iter().fold(Complex::<f64>::one(), |k, p| k*-p).re;
+                if order.is_multiple_of(2)
+                {
+                    k /= (1.0 + eps*eps).sqrt();
+                }
+                (vec![], p, k)
+            },
+            Prototype::ChebyshevII {attenuation} => {
+                if attenuation.is_nan() || attenuation <= 0.0
+                {
+                    return None
+                }
+                let mu = (10f64.powf(attenuation/10.0) - 1.0).sqrt().asinh()/n;
+                let z: Vec<_> = m().filter(|&m| m != 0.0)
+                    .map(|m| j/(PI*m/(2.0*n)).sin())
+                    .collect();
+                let p: Vec<_> = m().map(|m| {
+                        let p = -(j*PI*m/(2.0*n)).exp();
+                        Complex::new(mu.sinh()*p.re, mu.cosh()*p.im).inv()
+                    }).collect();
+                let k = (p.iter().fold(Complex::<f64>::one(), |k, p| k*-p)/z.iter().fold(Complex::<f64>::one(), |k, z| k*-z)).re;
+                (z, p, k)
+            },
+            Prototype::Bessel => {
+                // Reverse Bessel polynomial, scaled to be monic with a constant term of one
+                let mut a: Vec<f64> = vec![1.0];
+                for k in (0..order).rev()
+                {
+                    let prev = *a.last().unwrap();
+                    a.push(prev*((2*order - k)*(k + 1)) as f64/(2*(order - k)) as f64);
+                }
+                a.reverse();
+                let a0 = a[0];
+                let w = a0.powf(1.0/n);
+                let a: Vec<f64> = a.iter()
+                    .enumerate()
+                    .map(|(i, a)| a*w.powi(i as i32)/a0)
+                    .collect();
+                let p = Polynomial(a).roots();
+                (vec![], p, 1.0)
+            },
+            Prototype::Elliptic {ripple, attenuation} => {
+                if !(ripple > 0.0 && attenuation > ripple)
+                {
+                    return None
+                }
+                let ep = (10f64.powf(ripple/10.0) - 1.0).sqrt();
+                let es = (10f64.powf(attenuation/10.0) - 1.0).sqrt();
+                let k1 = ep/es;
+                let k1p = (1.0 - k1*k1).sqrt();
+
+                // Degree equation
+                let u: Vec<f64> = (1..=order/2).map(|i| (2*i - 1) as f64/n).collect();
+                let kp = k1p.powi(order as i32)*u.iter()
+                    .map(|&u| sne(Complex::from(u), k1p).re.powi(4))
+                    .product::<f64>();
+                let k = (1.0 - kp*kp).sqrt();
+
+                let v0 = -j*asne(j/ep, k1)/n;
+                let mut z = vec![];
+                let mut p = vec![];
+                for &u in u.iter()
+                {
+                    let zeta = cde(Complex::from(u), k);
+                    let zi = j/(zeta*k);
+                    z.extend([zi, zi.conj()]);
+                    let pi = j*cde(Complex::from(u) - j*v0, k);
+                    p.extend([pi, pi.conj()]);
+                }
+                if order % 2 == 1
+                {
+                    p.push(Complex::from((j*sne(j*v0, k)).re));
+                }
+                let h0 = if order.is_multiple_of(2) {10f64.powf(-ripple/20.0)} else {1.0};
+                let k = h0*(p.iter().fold(Complex::<f64>::one(), |k, p| k*-p)/z.iter().fold(Complex::<f64>::one(), |k, z| k*-z)).re;
+                (z, p, k)
+            }
+        };
+        Some((z, p, k))
+    }
+}
+
+impl Tf<{TfVar::S}>
+{
+    /// A lowpass prototype of the given order, with the cutoff `omega` in radians per second.
+    ///
+    /// `omega` may be numeric or symbolic.
+    /// Returns `None` if the order is zero or the ripple parameters are invalid.
+    pub fn prototype(prototype: Prototype, order: usize, omega: Coefficient) -> Option<Self>
+    {
+        let (z, p, k) = prototype.zpk(order)?;
+        let b: Vec<f64> = poly(&z).into_iter().map(|b| b*k).collect();
+        let a = poly(&p);
+
+        // H(s/omega), multiplied by omega^order
+        let mut pows = vec![Coefficient::one()];
+        for i in 0..order
+        {
+            pows.push(pows[i].clone()*omega.clone());
+        }
+        let scale = |c: Vec<f64>| Polynomial(c.into_iter()
+            .enumerate()
+            .map(|(i, c)| Coefficient::from(c)*pows[order - i].clone())
+            .collect());
+
+        let mut y = Tf(scale(b), scale(a));
+        y.simplify();
+        Some(y)
+    }
+}
+
+/// Expands the monic polynomial with the given roots, in ascending powers.
+///
+/// Roots with a positive imaginary part are paired with their conjugate, so that the coefficients are real.
+fn poly(roots: &[Complex<f64>]) -> Vec<f64>
+{
+    let tol = 1e-9;
+    let mut y = vec![1.0];
+    let mut mul = |f: &[f64]| {
+        let mut z = vec![0.0; y.len() + f.len() - 1];
+        for (i, y) in y.iter().enumerate()
+        {
+            for (j, f) in f.iter().enumerate()
+            {
+                z[i + j] += y*f;
+            }
+        }
+        y = z;
+    };
+    for r in roots
+    {
+        if r.im.abs() <= tol*r.norm().max(1.0)
+        {
+            mul(&[-r.re, 1.0]);
+        }
+        else if r.im > 0.0
+        {
+            mul(&[r.norm_sqr(), -2.0*r.re, 1.0]);
+        }
+    }
+    y
+}
+
+/// The descending Landen sequence of elliptic moduli.
+fn landen(mut k: f64) -> Vec<f64>
+{
+    let mut v = vec![];
+    while k > f64::EPSILON
+    {
+        k = (k/(1.0 + (1.0 - k*k).sqrt())).powi(2);
+        v.push(k);
+    }
+    v
+}
+
+/// The Jacobi elliptic function `cd(u*K, k)`.
+fn cde(u: Complex<f64>, k: f64) -> Complex<f64>
+{
+    let mut w = (u*FRAC_PI_2).cos();
+    for &kn in landen(k).iter().rev()
+    {
+        w = w*(1.0 + kn)/(w*w*kn + 1.0);
+    }
+    w
+}
+
+/// The Jacobi elliptic function `sn(u*K, k)`.
+fn sne(u: Complex<f64>, k: f64) -> Complex<f64>
+{
+    let mut w = (u*FRAC_PI_2).sin();
+    for &kn in landen(k).iter().rev()
+    {
+        w = w*(1.0 + kn)/(w*w*kn + 1.0);
+    }
+    w
+}
+
+/// The inverse of [cde](cde).
+fn acde(mut w: Complex<f64>, k: f64) -> Complex<f64>
+{
+    let mut k_prev = k;
+    for kn in landen(k)
+    {
+        w = w/((Complex::<f64>::one() - w*w*k_prev*k_prev).sqrt() + 1.0)*2.0/(1.0 + kn);
+        k_prev = kn;
+    }
+    w.acos()*2.0/PI
+}
+
+/// The inverse of [sne](sne).
+fn asne(w: Complex<f64>, k: f64) -> Complex<f64>
+{
+    Complex::<f64>::one() - acde(w, k)
+}
+
@@ -0,0 +1,194 @@
+use std::collections::BTreeSet;
+
+use num::{One, Zero};
+
+use crate::{simplify::Simplify, Tf, TfVar};
+
+/// A branch of a [SignalFlowGraph](SignalFlowGraph), from one node to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch<const VAR: TfVar>
+{
+    pub from: usize,
+    pub to: usize,
+    pub gain: Tf<VAR>
+}
+
+/// A signal flow graph with transfer functions as branch gains.
+///
+/// Paths and loops are given as lists of branch indices.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SignalFlowGraph<const VAR: TfVar>
+{
+    pub branches: Vec<Branch<VAR>>
+}
+
+impl<const VAR: TfVar> SignalFlowGraph<VAR>
+{
+    pub fn new() -> Self
+    {
+        Self {
+            branches: vec![]
+        }
+    }
+
+    /// Adds a branch, returning its index.
+    pub fn add_branch(&mut self, from: usize, to: usize, gain: Tf<VAR>) -> usize
+    {
+        self.branches.push(Branch {from, to, gain});
+        self.branches.len() - 1
+    }
+
+    /// The product of the gains of the branches.
+    pub fn path_gain(&self, path: &[usize]) -> Tf<VAR>
+    {
+        path.iter()
+            .fold(One::one(), |y: Tf<VAR>, &i| &y*&self.branches[i].gain)
+    }
+
+    /// All paths from `from` to `to` that visit no node more than once.
+    pub fn forward_paths(&self, from: usize, to: usize) -> Vec<Vec<usize>>
+    {
+        let mut paths = vec![];
+        let mut visited = BTreeSet::from([from]);
+        self.paths(from, to, &mut visited, &mut vec![], &mut paths, |_| true);
+        paths
+    }
+
+    /// All loops that visit no node more than once.
+    pub fn loops(&self) -> Vec<Vec<usize>>
+    {
+        let mut loops = vec![];
+        let starts: BTreeSet<usize> = self.branches.iter()
+            .map(|b| b.from)
+            .collect();
+        for start in starts
+        {
+            // Each loop is found from its lowest node only
+            let mut visited = BTreeSet::new();
+            self.paths(start, start, &mut visited, &mut vec![], &mut loops, |node| node > start);
+        }
+        loops
+    }
+
+    /// Sets of mutually non-touching loops, as indices into [loops](Self::loops), grouped by size.
+    ///
+    /// The first group contains every loop by itself, the second all non-touching pairs, and so on.
+    pub fn non_touching_loops(&self) -> Vec<Vec<Vec<usize>>>
+    {
+        let nodes = self.loop_nodes(&self.loops());
+        let mut groups = vec![];
+        non_touching(&nodes, &(0..nodes.len()).collect::<Vec<_>>(), 0, &mut vec![], &mut BTreeSet::new(), &mut groups);
+        groups
+    }
+
+    /// The graph determinant, `1 - sum(L1) + sum(L2) - ...`, over the sums of the gains of `k` mutually non-touching loops.
+    pub fn determinant(&self) -> Tf<VAR>
+    {
+        let loops = self.loops();
+        let nodes = self.loop_nodes(&loops);
+        self.delta(&loops, &nodes, &(0..loops.len()).collect::<Vec<_>>())
+    }
+
+    /// The transfer function from node `from` to node `to`, by Mason's gain formula.
+    pub fn gain(&self, from: usize, to: usize) -> Tf<VAR>
+    {
+        let loops = self.loops();
+        let nodes = self.loop_nodes(&loops);
+
+        let mut y: Tf<VAR> = Zero::zero();
+        for path in self.forward_paths(from, to)
+        {
+            let touched = self.path_nodes(&path);
+            let untouching: Vec<usize> = (0..loops.len())
+                .filter(|&i| nodes[i].is_disjoint(&touched))
+                .collect();
+            y = &y + &(&self.path_gain(&path)*&self.delta(&loops, &nodes, &untouching));
+        }
+        let mut y = &y/&self.determinant();
+        y.simplify();
+        y
+    }
+
+    fn paths<P>(&self, node: usize, to: usize, visited: &mut BTreeSet<usize>, path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>, allowed: P)
+    where
+        P: Fn(usize) -> bool + Copy
+    {
+        for (i, branch) in self.branches.iter().enumerate()
+        {
+            if branch.from != node
+            {
+                continue
+            }
+            path.push(i);
+            if branch.to == to
+            {
+                paths.push(path.clone());
+            }
+            else if allowed(branch.to) && visited.insert(branch.to)
+            {
+                self.paths(branch.to, to, visited, path, paths, allowed);
+                visited.remove(&branch.to);
+            }
+            path.pop();
+        }
+    }
+
+    fn path_nodes(&self, path: &[usize]) -> BTreeSet<usize>
+    {
+        path.iter()
+            .flat_map(|&i| [self.branches[i].from, self.branches[i].to])
+            .collect()
+    }
+
+    fn loop_nodes(&self, loops: &[Vec<usize>]) -> Vec<BTreeSet<usize>>
+    {
+        loops.iter()
+            .map(|l| self.path_nodes(l))
+            .collect()
+    }
+
+    fn delta(&self, loops: &[Vec<usize>], nodes: &[BTreeSet<usize>], allowed: &[usize]) -> Tf<VAR>
+    {
+        let mut groups = vec![];
+        non_touching(nodes, allowed, 0, &mut vec![], &mut BTreeSet::new(), &mut groups);
+
+        let mut y: Tf<VAR> = One::one();
+        for (k, group) in groups.into_iter().enumerate()
+        {
+            let sum = group.iter()
+                .map(|set| set.iter().fold(One::one(), |y: Tf<VAR>, &l| &y*&self.path_gain(&loops[l])))
+                .fold(Zero::zero(), |y: Tf<VAR>, x| &y + &x);
+            y = if k % 2 == 0 {&y - &sum} else {&y + &sum};
+        }
+        y
+    }
+}
+
+/// Collects the sets of mutually non-touching loops among `allowed`, grouped by size.
+fn non_touching(
+    nodes: &[BTreeSet<usize>],
+    allowed: &[usize],
+    start: usize,
+    set: &mut Vec<usize>,
+    touched: &mut BTreeSet<usize>,
+    groups: &mut Vec<Vec<Vec<usize>>>
+)
+{
+    for (j, &l) in allowed.iter().enumerate().skip(start)
+    {
+        if !nodes[l].is_disjoint(touched)
+        {
+            continue
+        }
+        set.push(l);
+        if groups.len() < set.len()
+        {
+            groups.push(vec![]);
+        }
+        groups[set.len() - 1].push(set.clone());
+
+        let mut touched = touched.union(&nodes[l]).copied().collect();
+        non_touching(nodes, allowed, j + 1, set, &mut touched, groups);
+        set.pop();
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use num::{Complex, Float, Zero};
+
+use crate::{compute::Compute, polynomial::Polynomial, Tf, TfVar};
+
+/// The order in which second-order sections are cascaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SosOrder
+{
+    /// The first section has the poles farthest from the unit circle.
+    #[default]
+    Up,
+    /// The first section has the poles closest to the unit circle.
+    Down
+}
+
+impl Tf<{TfVar::Z}>
+{
+    /// Evaluates the transfer function numerically and factors it into a cascade of second-order sections.
+    ///
+    /// Each section is given as `[b0, b1, b2, a0, a1, a2]` in powers of `z^-1`, with `a0 = 1`.
+    ///
+    /// Returns `None` if a symbol is missing from `syms`, or if the filter is not causal.
+    pub fn sos<F>(&self, syms: HashMap<&'static str, F>, order: SosOrder) -> Option<Vec<[F; 6]>>
+    where
+        F: Float
+    {
+        let (b, a) = self.compute(syms)?;
+        sos(&b, &a, order)
+    }
+}
+
+/// Factors a numeric transfer function in powers of `z^-1` into a cascade of second-order sections.
+///
+/// Poles are paired with their nearest zeros, starting with the poles closest to the unit circle.
+/// The gain is distributed evenly across all sections.
+pub fn sos<F>(b: &Polynomial<F>, a: &Polynomial<F>, order: SosOrder) -> Option<Vec<[F; 6]>>
+where
+    F: Float
+{
+    let mut b = b.0.clone();
+    let mut a = a.0.clone();
+    while b.last().is_some_and(|b| b.is_zero())
+    {
+        b.pop();
+    }
+    while a.last().is_some_and(|a| a.is_zero())
+    {
+        a.pop();
+    }
+    if a.first().is_none_or(|a| a.is_zero())
+    {
+        return None
+    }
+    if b.is_empty()
+    {
+        return Some(vec![[F::zero(), F::zero(), F::zero(), F::one(), F::zero(), F::zero()]])
+    }
+
+    let delay = b.iter()
+        .take_while(|b| b.is_zero())
+        .count();
+    let k = b[delay]/a[0];
+
+    let mut zeros = conjugate_pairs(Polynomial(b[delay..].iter().rev().copied().collect()).roots());
+    let mut poles = conjugate_pairs(Polynomial(a.iter().rev().copied().collect()).roots());
+
+    let n = poles.len().max(zeros.len() + delay).div_ceil(2).max(1);
+    zeros.resize(2*n, Zero::zero());
+    poles.resize(2*n, Zero::zero());
+
+    let mut sections = vec![];
+    while !poles.is_empty()
+    {
+        let p1 = poles.swap_remove(nearest(&poles, |p| (p.norm() - F::one()).abs()));
+        let p2 = if p1.im.is_zero()
+        {
+            poles.swap_remove(nearest(&poles, |p| if p.im.is_zero() {(p - p1).norm()} else {F::infinity()}))
+        }
+        else
+        {
+            poles.swap_remove(nearest(&poles, |p| (p - p1.conj()).norm()))
+        };
+
+        let z1 = zeros.swap_remove(nearest(&zeros, |z| (z - p1).norm()));
+        let z2 = if z1.im.is_zero()
+        {
+            zeros.swap_remove(nearest(&zeros, |z| if z.im.is_zero() {(z - p1).norm()} else {F::infinity()}))
+        }
+        else
+        {
+            zeros.swap_remove(nearest(&zeros, |z| (z - z1.conj()).norm()))
+        };
+
+        sections.push([
+            F::one(),
+            -(z1 + z2).re,
+            (z1*z2).re,
+            F::one(),
+            -(p1 + p2).re,
+            (p1*p2).re
+        ]);
+    }
+
+    // Delays take the place of zeros at the origin
+    let mut delay = delay;
+    for section in sections.iter_mut()
+    {
+        while delay > 0 && section[2].is_zero()
+        {
+            section[2] = section[1];
+            section[1] = section[0];
+            section[0] = F::zero();
+            delay -= 1;
+        }
+    }
+
+    let g = k.abs().powf(F::one()/F::from(n).unwrap());
+    for (i, section) in sections.iter_mut()
+        .enumerate()
+    {
+        let g = if i == 0 && k.is_sign_negative() {-g} else {g};
+        for b in section[..3].iter_mut()
+        {
+            *b = *b*g;
+        }
+    }
+
+    if order == SosOrder::Up
+    {
+        sections.reverse();
+    }
+
+    Some(sections)
+}
+
+fn nearest<F, D>(roots: &[Complex<F>], dist: D) -> usize
+where
+    F: Float,
+    D: Fn(Complex<F>) -> F
+{
+    roots.iter()
+        .enumerate()
+        .map(|(i, &r)| (i, dist(r)))
+        .fold((0, F::infinity()), |(i, d), (j, e)| if e < d {(j, e)} else {(i, d)})
+        .0
+}
+
+/// Snaps nearly real roots onto the real axis and makes complex roots exact conjugate pairs.
+pub(crate) fn conjugate_pairs<F>(mut roots: Vec<Complex<F>>) -> Vec<Complex<F>>
+where
+    F: Float
+{
+    let tol = F::epsilon().sqrt();
+    for r in roots.iter_mut()
+    {
+        if r.im.abs() <= tol*r.norm().max(F::one())
+        {
+            r.im = F::zero();
+        }
+    }
+    let mut paired = vec![];
+    let mut rest = vec![];
+    for r in roots
+    {
+        if r.im.is_zero()
+        {
+            paired.push(r)
+        }
+        else
+        {
+            rest.push(r)
+        }
+    }
+    while let Some(r) = rest.pop()
+    {
+        if rest.is_empty()
+        {
+            paired.push(Complex::from(r.re));
+            break
+        }
+        let c = rest.swap_remove(nearest(&rest, |c| (c - r.conj()).norm()));
+        let r = (r + c.conj())/F::from(2.0).unwrap();
+        paired.push(r);
+        paired.push(r.conj());
+    }
+    paired
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fmt::Display;
 
-use crate::{partial_zero::PartialZero, partial_one::PartialOne, simplify::Simplify};
+use num::Float;
+
+use crate::{partial_zero::PartialZero, partial_one::PartialOne, simplify::Simplify, compute::Compute};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub struct Sym(pub &'static str);
@@ -50,4 +53,24 @@ impl Display for Sym
     {
         self.0.fmt(f)
     }
+}
+
+impl<F> Compute<F> for Sym
+where
+    F: Float
+{
+    type Output = F;
+
+    fn compute(&self, syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        if PartialZero::is_zero(self)
+        {
+            return Some(F::zero())
+        }
+        if PartialOne::is_one(self)
+        {
+            return Some(F::one())
+        }
+        syms.get(self.0).copied()
+    }
 }
\ No newline at end of file
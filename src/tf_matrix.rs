@@ -0,0 +1,292 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num::{One, Zero};
+
+use crate::{interconnect::FeedbackSign, Tf, TfVar};
+
+/// A matrix of transfer functions, as rows, for systems with multiple inputs and outputs.
+///
+/// Entry `(i, j)` is the transfer function from input `j` to output `i`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TfMatrix<const VAR: TfVar>(pub Vec<Vec<Tf<VAR>>>);
+
+impl<const VAR: TfVar> TfMatrix<VAR>
+{
+    pub fn zeros(rows: usize, cols: usize) -> Self
+    {
+        TfMatrix(vec![vec![Zero::zero(); cols]; rows])
+    }
+
+    pub fn identity(n: usize) -> Self
+    {
+        let mut y = Self::zeros(n, n);
+        for i in 0..n
+        {
+            y.0[i][i] = One::one();
+        }
+        y
+    }
+
+    /// A matrix with a single column.
+    pub fn column(entries: Vec<Tf<VAR>>) -> Self
+    {
+        TfMatrix(entries.into_iter().map(|h| vec![h]).collect())
+    }
+
+    pub fn rows(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    /// The length of the first row.
+    pub fn cols(&self) -> usize
+    {
+        self.0.first().map(|row| row.len()).unwrap_or(0)
+    }
+
+    /// Whether all rows have the same length.
+    pub fn is_rectangular(&self) -> bool
+    {
+        self.0.iter().all(|row| row.len() == self.cols())
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the rows differ in length, see [`TfMatrix::is_rectangular`].
+    pub fn transpose(&self) -> Self
+    {
+        assert!(self.is_rectangular(), "Ragged matrix");
+        TfMatrix((0..self.cols())
+            .map(|j| self.0.iter().map(|row| row[j].clone()).collect())
+            .collect())
+    }
+
+    /// The matrix without row `i` and column `j`.
+    pub fn minor(&self, i: usize, j: usize) -> Self
+    {
+        TfMatrix(self.0.iter()
+            .enumerate()
+            .filter(|&(k, _)| k != i)
+            .map(|(_, row)| row.iter()
+                .enumerate()
+                .filter(|&(l, _)| l != j)
+                .map(|(_, h)| h.clone())
+                .collect()
+            ).collect())
+    }
+
+    /// The determinant, by cofactor expansion along the first row.
+    ///
+    /// Returns `None` if the matrix is not square.
+    pub fn determinant(&self) -> Option<Tf<VAR>>
+    {
+        let n = self.rows();
+        if self.cols() != n && n != 0 || !self.is_rectangular()
+        {
+            return None
+        }
+        match n
+        {
+            0 => return Some(One::one()),
+            1 => return Some(self.0[0][0].clone()),
+            _ => ()
+        }
+        let mut y: Tf<VAR> = Zero::zero();
+        for (j, h) in self.0[0].iter().enumerate()
+        {
+            if Zero::is_zero(h)
+            {
+                continue
+            }
+            let x = h*&self.minor(0, j).determinant()?;
+            y = if j % 2 == 0 {&y + &x} else {&y - &x};
+        }
+        Some(y)
+    }
+
+    /// The transpose of the matrix of cofactors.
+    ///
+    /// Returns `None` if the matrix is not square.
+    pub fn adjugate(&self) -> Option<Self>
+    {
+        let n = self.rows();
+        if self.cols() != n || !self.is_rectangular()
+        {
+            return None
+        }
+        let mut y = Self::zeros(n, n);
+        for i in 0..n
+        {
+            for j in 0..n
+            {
+                let c = self.minor(i, j).determinant()?;
+                y.0[j][i] = if (i + j) % 2 == 0 {c} else {-c};
+            }
+        }
+        Some(y)
+    }
+
+    /// The inverse, as the adjugate divided by the determinant.
+    ///
+    /// Returns `None` if the matrix is not square or the determinant is zero.
+    pub fn inverse(&self) -> Option<Self>
+    {
+        let det = self.determinant()?;
+        if Zero::is_zero(&det)
+        {
+            return None
+        }
+        let adj = self.adjugate()?;
+        Some(TfMatrix(adj.0.into_iter()
+            .map(|row| row.into_iter().map(|h| &h/&det).collect())
+            .collect()))
+    }
+
+    /// The sum, or `None` if the dimensions differ or either matrix is ragged.
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self>
+    {
+        self.zip_with(rhs, |a, b| a + b)
+    }
+
+    /// The difference, or `None` if the dimensions differ or either matrix is ragged.
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self>
+    {
+        self.zip_with(rhs, |a, b| a - b)
+    }
+
+    /// The product, or `None` if the columns of `self` don't match the rows of `rhs`, or either is ragged.
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self>
+    {
+        if self.cols() != rhs.rows() || !self.is_rectangular() || !rhs.is_rectangular()
+        {
+            return None
+        }
+        Some(TfMatrix(self.0.iter()
+            .map(|row| (0..rhs.cols())
+                .map(|j| row.iter()
+                    .zip(rhs.0.iter())
+                    .filter(|(a, b)| !Zero::is_zero(*a) && !Zero::is_zero(&b[j]))
+                    .fold(Zero::zero(), |y: Tf<VAR>, (a, b)| &y + &(a*&b[j]))
+                ).collect()
+            ).collect()))
+    }
+
+    fn zip_with(&self, rhs: &Self, f: impl Fn(&Tf<VAR>, &Tf<VAR>) -> Tf<VAR>) -> Option<Self>
+    {
+        if self.rows() != rhs.rows() || self.cols() != rhs.cols() || !self.is_rectangular() || !rhs.is_rectangular()
+        {
+            return None
+        }
+        Some(TfMatrix(self.0.iter()
+            .zip(rhs.0.iter())
+            .map(|(a, b)| a.iter().zip(b.iter()).map(|(a, b)| f(a, b)).collect())
+            .collect()))
+    }
+
+    /// Closes a loop with `self` in the forward path and `h` in the feedback path, `(I -+ G*H)^-1*G`.
+    ///
+    /// Returns `None` if the dimensions do not match or the loop is singular.
+    pub fn feedback(&self, h: &Self, sign: FeedbackSign) -> Option<Self>
+    {
+        if h.rows() != self.cols() || h.cols() != self.rows()
+        {
+            return None
+        }
+        let loop_gain = self.checked_mul(h)?;
+        let i = Self::identity(self.rows());
+        let x = match sign
+        {
+            FeedbackSign::Negative => i.checked_add(&loop_gain)?,
+            FeedbackSign::Positive => i.checked_sub(&loop_gain)?
+        };
+        x.inverse()?.checked_mul(self)
+    }
+}
+
+impl TfMatrix<{TfVar::S}>
+{
+    pub fn bilinear_transform(self) -> TfMatrix<{TfVar::Z}>
+    {
+        TfMatrix(self.0.into_iter()
+            .map(|row| row.into_iter().map(|h| h.bilinear_transform()).collect())
+            .collect())
+    }
+}
+
+impl<const VAR: TfVar> Add for &TfMatrix<VAR>
+{
+    type Output = TfMatrix<VAR>;
+
+    /// # Panics
+    ///
+    /// Panics if the dimensions differ, see [`TfMatrix::checked_add`].
+    fn add(self, rhs: Self) -> Self::Output
+    {
+        self.checked_add(rhs).expect("Dimension mismatch")
+    }
+}
+impl<const VAR: TfVar> Add for TfMatrix<VAR>
+{
+    type Output = TfMatrix<VAR>;
+
+    fn add(self, rhs: Self) -> Self::Output
+    {
+        &self + &rhs
+    }
+}
+
+impl<const VAR: TfVar> Sub for &TfMatrix<VAR>
+{
+    type Output = TfMatrix<VAR>;
+
+    /// # Panics
+    ///
+    /// Panics if the dimensions differ, see [`TfMatrix::checked_sub`].
+    fn sub(self, rhs: Self) -> Self::Output
+    {
+        self.checked_sub(rhs).expect("Dimension mismatch")
+    }
+}
+impl<const VAR: TfVar> Sub for TfMatrix<VAR>
+{
+    type Output = TfMatrix<VAR>;
+
+    fn sub(self, rhs: Self) -> Self::Output
+    {
+        &self - &rhs
+    }
+}
+
+impl<const VAR: TfVar> Mul for &TfMatrix<VAR>
+{
+    type Output = TfMatrix<VAR>;
+
+    /// # Panics
+    ///
+    /// Panics if the columns of `self` don't match the rows of `rhs`, see [`TfMatrix::checked_mul`].
+    fn mul(self, rhs: Self) -> Self::Output
+    {
+        self.checked_mul(rhs).expect("Dimension mismatch")
+    }
+}
+impl<const VAR: TfVar> Mul for TfMatrix<VAR>
+{
+    type Output = TfMatrix<VAR>;
+
+    fn mul(self, rhs: Self) -> Self::Output
+    {
+        &self*&rhs
+    }
+}
+
+impl<const VAR: TfVar> Neg for TfMatrix<VAR>
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output
+    {
+        TfMatrix(self.0.into_iter()
+            .map(|row| row.into_iter().map(|h| -h).collect())
+            .collect())
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use num::{Complex, Float, Zero};
+
+use crate::{compute::Compute, iir_filter::{IirFilter, IirStructure}, polynomial::Polynomial, Tf, TfVar};
+
+impl Tf<{TfVar::Z}>
+{
+    /// Computes the first `n` samples of the impulse response by running the difference equation.
+    pub fn impulse_response<F>(&self, syms: HashMap<&'static str, F>, n: usize) -> Option<Vec<F>>
+    where
+        F: Float
+    {
+        let mut filter = IirFilter::new(self, syms, IirStructure::DirectFormIITransposed)?;
+        Some((0..n).map(|i| filter.process_sample(if i == 0 {F::one()} else {F::zero()}))
+            .collect())
+    }
+
+    /// Computes the first `n` samples of the unit step response by running the difference equation.
+    pub fn step_response<F>(&self, syms: HashMap<&'static str, F>, n: usize) -> Option<Vec<F>>
+    where
+        F: Float
+    {
+        let mut filter = IirFilter::new(self, syms, IirStructure::DirectFormIITransposed)?;
+        Some((0..n).map(|_| filter.process_sample(F::one()))
+            .collect())
+    }
+}
+
+impl Tf<{TfVar::S}>
+{
+    /// Computes the impulse response at the times `t` from the residues of the numerically computed poles.
+    ///
+    /// Any direct feedthrough term is a Dirac impulse at `t = 0`, and is left out.
+    pub fn impulse_response<F>(&self, syms: HashMap<&'static str, F>, t: &[F]) -> Option<Vec<F>>
+    where
+        F: Float
+    {
+        let (b, a) = self.compute(syms)?;
+        impulse_response(b, a, t)
+    }
+
+    /// Computes the unit step response at the times `t` as the impulse response of `H(s)/s`.
+    pub fn step_response<F>(&self, syms: HashMap<&'static str, F>, t: &[F]) -> Option<Vec<F>>
+    where
+        F: Float
+    {
+        let (b, a) = self.compute(syms)?;
+        impulse_response(b, a << 1, t)
+    }
+}
+
+fn impulse_response<F>(b: Polynomial<F>, a: Polynomial<F>, t: &[F]) -> Option<Vec<F>>
+where
+    F: Float
+{
+    let mut a = a.0;
+    while a.last().is_some_and(|a| a.is_zero())
+    {
+        a.pop();
+    }
+    let lead = *a.last()?;
+
+    // Strictly proper remainder
+    let mut r = b.0;
+    while r.len() >= a.len()
+    {
+        let q = *r.last().unwrap()/lead;
+        let dn = r.len() - a.len();
+        for (i, &a) in a.iter().enumerate()
+        {
+            r[i + dn] = r[i + dn] - q*a;
+        }
+        r.pop();
+    }
+    let r = Polynomial(r.into_iter().map(Complex::from).collect::<Vec<_>>());
+
+    // Group repeated poles
+    let tol = F::epsilon().sqrt().sqrt();
+    let mut poles: Vec<(Complex<F>, usize)> = vec![];
+    for p in Polynomial(a).roots()
+    {
+        match poles.iter_mut().find(|(q, _)| (p - *q).norm() <= tol*q.norm().max(F::one()))
+        {
+            Some((_, m)) => *m += 1,
+            None => poles.push((p, 1))
+        }
+    }
+
+    // Partial fraction coefficients c[k] of 1/(s - p)^(k + 1)
+    let residues: Vec<(Complex<F>, Vec<Complex<F>>)> = poles.iter()
+        .map(|&(p, m)| {
+            let mut rest = vec![Complex::from(lead)];
+            for &(q, l) in poles.iter()
+            {
+                if q != p
+                {
+                    for _ in 0..l
+                    {
+                        rest.insert(0, Complex::zero());
+                        for i in 0..rest.len() - 1
+                        {
+                            rest[i] = rest[i] - q*rest[i + 1];
+                        }
+                    }
+                }
+            }
+            let rt = taylor_shift(&r.0, p);
+            let at = taylor_shift(&rest, p);
+            let mut series: Vec<Complex<F>> = vec![];
+            for k in 0..m
+            {
+                let mut x = rt.get(k).copied().unwrap_or(Complex::zero());
+                for j in 1..=k
+                {
+                    x = x - at.get(j).copied().unwrap_or(Complex::zero())*series[k - j];
+                }
+                series.push(x/at[0]);
+            }
+            series.reverse();
+            (p, series)
+        }).collect();
+
+    Some(t.iter()
+        .map(|&t| {
+            let mut h = Complex::zero();
+            for (p, c) in residues.iter()
+            {
+                let e = (*p*t).exp();
+                let mut tk = F::one();
+                for (k, &c) in c.iter().enumerate()
+                {
+                    h = h + c*e*tk;
+                    tk = tk*t/F::from(k + 1).unwrap();
+                }
+            }
+            h.re
+        }).collect())
+}
+
+/// The coefficients of `P(p + u)` in powers of `u`.
+fn taylor_shift<F>(c: &[Complex<F>], p: Complex<F>) -> Vec<Complex<F>>
+where
+    F: Float
+{
+    let mut c = c.to_vec();
+    let n = c.len();
+    for i in 0..n
+    {
+        for j in (i..n.saturating_sub(1)).rev()
+        {
+            c[j] = c[j] + p*c[j + 1];
+        }
+    }
+    c
+}
@@ -1,8 +1,8 @@
 use std::{marker::ConstParamTy, ops::{Add, Sub, AddAssign, Mul, Div, Neg}, fmt::Display, collections::{HashMap, BTreeMap}};
 
-use num::{traits::Inv, One, Zero};
+use num::{traits::Inv, One, Zero, Float};
 
-use crate::{powered_product::PoweredProduct, polynomial::Polynomial, coefficient::Coefficient, partial_one::PartialOne, partial_zero::PartialZero, Int, simplify::Simplify, sym::Sym, weighted_sum::WeightedSum};
+use crate::{compute::Compute, context, powered_product::PoweredProduct, polynomial::Polynomial, coefficient::Coefficient, partial_one::PartialOne, partial_zero::PartialZero, Int, simplify::Simplify, sym::Sym, weighted_sum::WeightedSum};
 
 #[derive(ConstParamTy, PartialEq, Eq)]
 pub enum TfVar
@@ -43,7 +43,7 @@ impl TfVar
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Tf<const VAR: TfVar>(pub Polynomial<Coefficient>, pub Polynomial<Coefficient>);
 
 impl<const VAR: TfVar> Tf<VAR>
@@ -52,6 +52,88 @@ impl<const VAR: TfVar> Tf<VAR>
     {
         self.0.order().max(self.1.order())
     }
+
+    /// Differentiates with respect to the symbol `sym` using the quotient rule.
+    pub fn diff_sym(&self, sym: Sym) -> Self
+    {
+        let mut y = Tf(
+            &self.0.diff_sym(sym)*&self.1 - &self.0*&self.1.diff_sym(sym),
+            &self.1*&self.1
+        );
+        y.simplify();
+        y
+    }
+
+    /// Binds the symbols in `syms` to numbers, returning a simplified transfer function in the remaining symbols.
+    pub fn partial_eval<F>(&self, syms: HashMap<&'static str, F>) -> Self
+    where
+        F: Float
+    {
+        let mut y = Tf(
+            self.0.partial_eval(syms.clone()),
+            self.1.partial_eval(syms)
+        );
+        y.simplify();
+        y
+    }
+
+    /// Substitutes the transfer function `value` for the symbol `sym`, simplifying the result.
+    ///
+    /// Numerator and denominator are multiplied by the powers of `value` needed to keep both polynomial,
+    /// so `sym` may appear with negative powers.
+    pub fn substitute(&self, sym: Sym, value: &Tf<VAR>) -> Self
+    {
+        let b: Vec<BTreeMap<Int, Coefficient>> = self.0.0.iter()
+            .map(|b| b.collect(sym))
+            .collect();
+        let a: Vec<BTreeMap<Int, Coefficient>> = self.1.0.iter()
+            .map(|a| a.collect(sym))
+            .collect();
+        let p_min = b.iter().chain(a.iter())
+            .flat_map(|c| c.keys().next())
+            .fold(0, |p_min, &p| p_min.min(p));
+        let p_max = b.iter().chain(a.iter())
+            .flat_map(|c| c.keys().next_back())
+            .fold(0, |p_max, &p| p_max.max(p));
+
+        let pows = |x: &Polynomial<Coefficient>| {
+            let mut pows: Vec<Polynomial<Coefficient>> = vec![PartialOne::one()];
+            for p in 0..(p_max - p_min) as usize
+            {
+                pows.push(&pows[p]*x);
+            }
+            pows
+        };
+        let num_pows = pows(&value.0);
+        let den_pows = pows(&value.1);
+
+        let expand = |c: &[BTreeMap<Int, Coefficient>]| {
+            let mut y: Polynomial<Coefficient> = PartialZero::zero();
+            for (i, c) in c.iter().enumerate()
+            {
+                for (&p, c) in c.iter()
+                {
+                    y += ((&num_pows[(p - p_min) as usize]*&den_pows[(p_max - p) as usize])*c.clone()) << i;
+                }
+            }
+            y
+        };
+
+        let mut y = Tf(expand(&b), expand(&a));
+        y.simplify();
+        y
+    }
+
+    /// The normalized sensitivity of the transfer function to the symbol `sym`, `(x/H)*dH/dx`.
+    pub fn sensitivity(&self, sym: Sym) -> Self
+    {
+        let mut y = Tf(
+            (&self.0.diff_sym(sym)*&self.1 - &self.0*&self.1.diff_sym(sym))*Coefficient::from(sym),
+            &self.0*&self.1
+        );
+        y.simplify();
+        y
+    }
 }
 
 impl Tf<{TfVar::S}>
@@ -71,8 +153,7 @@ impl Tf<{TfVar::S}>
     pub fn bilinear_transform(self) -> Tf<{TfVar::Z}>
     {
         let order = self.order();
-
-        let mut tfz = Tf(PartialZero::zero(), PartialZero::zero());
+        context::report_degree(order);
 
         let p: Vec<Polynomial<Coefficient>> = (0..=order).map(|i| {
             let mut p = Polynomial(vec![Coefficient::from(1)]);
@@ -89,21 +170,87 @@ impl Tf<{TfVar::S}>
             p
         }).collect();
 
-        for (b, p) in self.0.0.iter()
-            .zip(p.clone().into_iter())
+        // Every coefficient in z is a sum of products, computed in one go
+        let expand = |c: &Polynomial<Coefficient>| Polynomial((0..if c.0.is_empty() {0} else {order + 1})
+            .map(|n| Coefficient::dot(&c.0.iter()
+                .zip(p.iter())
+                .map(|(c, p)| (&p.0[n], c))
+                .collect::<Vec<_>>()
+            )).collect());
+
+        Tf(expand(&self.0), expand(&self.1))
+    }
+}
+
+impl Tf<{TfVar::Z}>
+{
+    /// `z^p`, where negative powers are delays.
+    pub fn z(p: isize) -> Self
+    {
+        if p <= 0
         {
-            let b: Polynomial<Coefficient> = p*b.clone();
-            tfz.0 += b;
+            Self(Polynomial::one() << (-p) as usize, PartialOne::one())
         }
-        
-        for (a, p) in self.1.0.iter()
-            .zip(p.into_iter())
+        else
         {
-            let a: Polynomial<Coefficient> = p*a.clone();
-            tfz.1 += a;
+            Self(Polynomial::one(), Polynomial::one() << p as usize)
         }
+    }
+
+    /// A delay of `n` samples, `z^-n`.
+    pub fn delay(n: usize) -> Self
+    {
+        Self(Polynomial::one() << n, PartialOne::one())
+    }
+
+    /// A FIR filter with the taps `b`, where `b[n]` multiplies `z^-n`.
+    pub fn fir(b: &[Coefficient]) -> Self
+    {
+        Self(Polynomial(b.to_vec()), PartialOne::one())
+    }
+
+    /// An IIR filter with the coefficients `b` and `a`, where `b[n]` and `a[n]` multiply `z^-n`.
+    pub fn iir(b: &[Coefficient], a: &[Coefficient]) -> Self
+    {
+        Self(Polynomial(b.to_vec()), Polynomial(a.to_vec()))
+    }
+
+    /// Displays the transfer function in powers of `z^-1`.
+    pub fn z_inv(&self) -> ZInv<'_>
+    {
+        ZInv(self)
+    }
+}
+
+/// Displays a z-domain [Tf](Tf) in powers of `z^-1`.
+pub struct ZInv<'a>(pub &'a Tf<{TfVar::Z}>);
+
+impl Display for ZInv<'_>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        self.0.fmt_var(f, &|f, k| {
+            if k != 0
+            {
+                write!(f, "z^-{}", k)?;
+            }
+            Ok(())
+        })
+    }
+}
 
-        tfz
+impl<F, const VAR: TfVar> Compute<F> for Tf<VAR>
+where
+    F: Float
+{
+    type Output = (Polynomial<F>, Polynomial<F>);
+
+    fn compute(&self, syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        Some((
+            self.0.compute(syms.clone())?,
+            self.1.compute(syms)?
+        ))
     }
 }
 
@@ -114,6 +261,13 @@ impl<const VAR: TfVar> From<&'static str> for Tf<VAR>
         Tf(Polynomial::from(Coefficient::from(value)), Polynomial::one())
     }
 }
+impl<const VAR: TfVar> From<Coefficient> for Tf<VAR>
+{
+    fn from(value: Coefficient) -> Self
+    {
+        Tf(Polynomial::from(value), Polynomial::one())
+    }
+}
 impl<const VAR: TfVar> From<Int> for Tf<VAR>
 {
     fn from(value: Int) -> Self
@@ -139,7 +293,7 @@ impl<const VAR: TfVar> Add for &Tf<VAR>
         if self.1 == rhs.1
         {
             return Tf(
-                self.0.clone() + rhs.1.clone(),
+                self.0.clone() + rhs.0.clone(),
                 self.1.clone()
             )
         }
@@ -232,7 +386,7 @@ impl<const VAR: TfVar> Sub for &Tf<VAR>
         if self.1 == rhs.1
         {
             return Tf(
-                self.0.clone() - rhs.1.clone(),
+                self.0.clone() - rhs.0.clone(),
                 self.1.clone()
             )
         }
@@ -499,13 +653,21 @@ impl<const VAR: TfVar> Zero for Tf<VAR>
     fn set_zero(&mut self)
     {
         PartialZero::set_zero(&mut self.0);
-        PartialOne::set_one(&mut self.0);
+        PartialOne::set_one(&mut self.1);
     }
 }
 
 impl<const VAR: TfVar> Display for Tf<VAR>
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        self.fmt_var(f, VAR.fmt())
+    }
+}
+
+impl<const VAR: TfVar> Tf<VAR>
+{
+    fn fmt_var(&self, f: &mut std::fmt::Formatter<'_>, var_fmt: &dyn Fn(&mut std::fmt::Formatter<'_>, usize) -> std::fmt::Result) -> std::fmt::Result
     {
         let b_parens = self.0.0.iter().filter(|&b| !PartialZero::is_zero(b)).count() > 1;
         let a_parens = self.1.0.iter().filter(|&a| !PartialZero::is_zero(a)).count() > 1;
@@ -515,8 +677,6 @@ impl<const VAR: TfVar> Display for Tf<VAR>
             write!(f, "(")?;
         }
 
-        let var_fmt = VAR.fmt();
-
         self.0.fmt(f, var_fmt)?;
         
         if b_parens
@@ -580,6 +740,7 @@ impl<const VAR: TfVar> Simplify for Tf<VAR>
 
     fn simplify(&mut self)
     {
+        context::report_degree(self.order());
         while match (self.0.0.first(), self.1.0.first())
         {
             (Some(b), Some(a)) => PartialZero::is_zero(b) && PartialZero::is_zero(a),
@@ -0,0 +1,301 @@
+use std::fmt::Display;
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+
+use num::rational::Ratio;
+use num::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, ToPrimitive, Zero};
+
+use crate::{Int, simplify::Simplify};
+
+/// The numeric weight of a term in a [Coefficient](crate::coefficient::Coefficient).
+///
+/// Weights are exact integers, or exact ratios after an inexact division.
+/// They become floats when an operation overflows or a symbol is bound to a number.
+///
+/// Weights are only [PartialEq](PartialEq), since a float weight can be NaN.
+#[derive(Debug, Clone, Copy)]
+pub enum Weight
+{
+    Int(Int),
+    /// A ratio in lowest terms, which is never an integer.
+    Ratio(Ratio<Int>),
+    Float(f64)
+}
+
+impl Weight
+{
+    pub fn abs(&self) -> Self
+    {
+        match *self
+        {
+            Weight::Int(i) => i.checked_abs().map(Weight::Int).unwrap_or(Weight::Float((i as f64).abs())),
+            Weight::Ratio(r) => if r < Ratio::zero() {-*self} else {*self},
+            Weight::Float(x) => Weight::Float(x.abs())
+        }
+    }
+
+    pub fn is_negative(&self) -> bool
+    {
+        match *self
+        {
+            Weight::Int(i) => i < 0,
+            Weight::Ratio(r) => *r.numer() < 0,
+            Weight::Float(x) => x < 0.0
+        }
+    }
+
+    fn float(&self) -> f64
+    {
+        match *self
+        {
+            Weight::Int(i) => i as f64,
+            Weight::Ratio(r) => *r.numer() as f64 / *r.denom() as f64,
+            Weight::Float(x) => x
+        }
+    }
+
+    fn ratio(&self) -> Option<Ratio<Int>>
+    {
+        match *self
+        {
+            Weight::Int(i) => Some(Ratio::from_integer(i)),
+            Weight::Ratio(r) => Some(r),
+            Weight::Float(_) => None
+        }
+    }
+
+    /// Applies an exact operation on ratios, falling back to floats if either is a float or the operation overflows.
+    fn exact<R, F>(self, rhs: Self, r: R, f: F) -> Self
+    where
+        R: FnOnce(Ratio<Int>, Ratio<Int>) -> Option<Ratio<Int>>,
+        F: FnOnce(f64, f64) -> f64
+    {
+        match self.ratio().zip(rhs.ratio()).and_then(|(a, b)| r(a, b))
+        {
+            Some(y) => y.into(),
+            None => Weight::Float(f(self.float(), rhs.float()))
+        }
+    }
+}
+
+impl From<Int> for Weight
+{
+    fn from(i: Int) -> Self
+    {
+        Weight::Int(i)
+    }
+}
+impl From<Ratio<Int>> for Weight
+{
+    fn from(r: Ratio<Int>) -> Self
+    {
+        if r.is_integer()
+        {
+            Weight::Int(r.to_integer())
+        }
+        else
+        {
+            Weight::Ratio(r)
+        }
+    }
+}
+impl From<f64> for Weight
+{
+    fn from(x: f64) -> Self
+    {
+        Weight::Float(x)
+    }
+}
+
+impl PartialEq for Weight
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        match (self, other)
+        {
+            (Weight::Int(a), Weight::Int(b)) => a == b,
+            _ => match self.ratio().zip(other.ratio())
+            {
+                Some((a, b)) => a == b,
+                None => self.float() == other.float()
+            }
+        }
+    }
+}
+
+impl Add for Weight
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output
+    {
+        match (self, rhs)
+        {
+            (Weight::Int(a), Weight::Int(b)) => a.checked_add(b).map(Weight::Int).unwrap_or(Weight::Float(a as f64 + b as f64)),
+            _ => self.exact(rhs, |a, b| a.checked_add(&b), |a, b| a + b)
+        }
+    }
+}
+impl Sub for Weight
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output
+    {
+        match (self, rhs)
+        {
+            (Weight::Int(a), Weight::Int(b)) => a.checked_sub(b).map(Weight::Int).unwrap_or(Weight::Float(a as f64 - b as f64)),
+            _ => self.exact(rhs, |a, b| a.checked_sub(&b), |a, b| a - b)
+        }
+    }
+}
+impl Mul for Weight
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output
+    {
+        match (self, rhs)
+        {
+            (Weight::Int(a), Weight::Int(b)) => a.checked_mul(b).map(Weight::Int).unwrap_or(Weight::Float(a as f64*b as f64)),
+            _ => self.exact(rhs, |a, b| a.checked_mul(&b), |a, b| a*b)
+        }
+    }
+}
+impl Div for Weight
+{
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output
+    {
+        match (self, rhs)
+        {
+            (Weight::Int(a), Weight::Int(b)) if b != 0 && a.checked_rem(b) == Some(0) => Weight::Int(a/b),
+            _ => self.exact(rhs, |a, b| if b.is_zero() {None} else {a.checked_div(&b)}, |a, b| a/b)
+        }
+    }
+}
+impl Neg for Weight
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output
+    {
+        match self
+        {
+            Weight::Int(i) => i.checked_neg().map(Weight::Int).unwrap_or(Weight::Float(-(i as f64))),
+            Weight::Ratio(r) => r.numer().checked_neg()
+                .map(|n| Weight::Ratio(Ratio::new_raw(n, *r.denom())))
+                .unwrap_or(Weight::Float(-self.float())),
+            Weight::Float(x) => Weight::Float(-x)
+        }
+    }
+}
+
+impl AddAssign for Weight
+{
+    fn add_assign(&mut self, rhs: Self)
+    {
+        *self = *self + rhs
+    }
+}
+impl SubAssign for Weight
+{
+    fn sub_assign(&mut self, rhs: Self)
+    {
+        *self = *self - rhs
+    }
+}
+impl MulAssign for Weight
+{
+    fn mul_assign(&mut self, rhs: Self)
+    {
+        *self = *self*rhs
+    }
+}
+impl DivAssign for Weight
+{
+    fn div_assign(&mut self, rhs: Self)
+    {
+        *self = *self/rhs
+    }
+}
+
+impl Zero for Weight
+{
+    fn zero() -> Self
+    {
+        Weight::Int(0)
+    }
+
+    fn is_zero(&self) -> bool
+    {
+        self.float() == 0.0
+    }
+}
+
+impl One for Weight
+{
+    fn one() -> Self
+    {
+        Weight::Int(1)
+    }
+
+    fn is_one(&self) -> bool
+    {
+        self.float() == 1.0
+    }
+}
+
+impl ToPrimitive for Weight
+{
+    fn to_i64(&self) -> Option<i64>
+    {
+        match *self
+        {
+            Weight::Int(i) => i.to_i64(),
+            Weight::Ratio(r) => r.to_i64(),
+            Weight::Float(x) => x.to_i64()
+        }
+    }
+
+    fn to_u64(&self) -> Option<u64>
+    {
+        match *self
+        {
+            Weight::Int(i) => i.to_u64(),
+            Weight::Ratio(r) => r.to_u64(),
+            Weight::Float(x) => x.to_u64()
+        }
+    }
+
+    fn to_f64(&self) -> Option<f64>
+    {
+        Some(self.float())
+    }
+}
+
+impl Simplify for Weight
+{
+    fn is_simplified(&self) -> bool
+    {
+        true
+    }
+
+    fn simplify(&mut self)
+    {
+
+    }
+}
+
+impl Display for Weight
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match *self
+        {
+            Weight::Int(i) => write!(f, "{:.1}", i as f64),
+            Weight::Ratio(r) => write!(f, "{:.1}/{:.1}", *r.numer() as f64, *r.denom() as f64),
+            Weight::Float(x) => write!(f, "{:?}", x)
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use std::{collections::{HashMap, BTreeMap}, ops::{Add, AddAssign, SubAssign, Neg, Sub, MulAssign, DivAssign, Mul, Div}, hash::Hash, process::Output};
 
-use num::{One, Zero};
+use num::{One, Zero, Float, NumCast, ToPrimitive};
 
-use crate::{partial_one::PartialOne, partial_zero::PartialZero, simplify::Simplify};
+use crate::{partial_one::PartialOne, partial_zero::PartialZero, simplify::Simplify, compute::Compute, parallel};
+
+/// The least number of term products per thread.
+const PARALLEL_MIN_PRODUCTS: usize = 64;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct WeightedSum<E, W>(pub BTreeMap<E, W>)
@@ -247,31 +250,49 @@ where
 impl<E1, E2, W1, W2> Mul<&WeightedSum<E2, W2>> for &WeightedSum<E1, W1>
 where
     WeightedSum<<E1 as Mul<E2>>::Output, <W1 as Mul<W2>>::Output>: PartialZero + Simplify,
-    E1: Mul<E2> + Eq + Hash + Ord + Clone,
-    W1: Mul<W2> + Clone,
-    E2: Eq + Hash + Ord + Clone,
-    W2: Clone,
-    <E1 as Mul<E2>>::Output: Eq + Hash + Ord,
-    <W1 as Mul<W2>>::Output: AddAssign
+    E1: Mul<E2> + Eq + Hash + Ord + Clone + Sync,
+    W1: Mul<W2> + Clone + Sync,
+    E2: Eq + Hash + Ord + Clone + Sync,
+    W2: Clone + Sync,
+    <E1 as Mul<E2>>::Output: Eq + Hash + Ord + Send,
+    <W1 as Mul<W2>>::Output: AddAssign + Send
 {
     type Output = WeightedSum<<E1 as Mul<E2>>::Output, <W1 as Mul<W2>>::Output>;
 
     fn mul(self, rhs: &WeightedSum<E2, W2>) -> Self::Output
     {
         let mut y = Self::Output::zero();
-        for (e1, w1) in self.0.iter()
+        let mut merge = |e, w| match y.0.get_mut(&e)
         {
-            for (e2, w2) in rhs.0.iter()
+            Some(m) => *m += w,
+            None => {y.0.insert(e, w);}
+        };
+
+        let min_chunk = PARALLEL_MIN_PRODUCTS.div_ceil(rhs.0.len().max(1));
+        if parallel::chunk_threads(self.0.len(), min_chunk) == 1
+        {
+            for (e1, w1) in self.0.iter()
             {
-                let e = e1.clone()*e2.clone();
-                let w = w1.clone()*w2.clone();
-                match y.0.get_mut(&e)
+                for (e2, w2) in rhs.0.iter()
                 {
-                    Some(m) => *m += w,
-                    None => {y.0.insert(e, w);}
+                    merge(e1.clone()*e2.clone(), w1.clone()*w2.clone());
                 }
             }
         }
+        else
+        {
+            // Products are formed in parallel, but summed in the serial order
+            let lhs: Vec<_> = self.0.iter().collect();
+            let products = parallel::map_chunks(lhs.len(), min_chunk, |i| lhs[i].iter()
+                .flat_map(|(e1, w1)| rhs.0.iter()
+                    .map(|(e2, w2)| ((*e1).clone()*e2.clone(), (*w1).clone()*w2.clone()))
+                ).collect::<Vec<_>>()
+            );
+            for (e, w) in products.into_iter().flatten()
+            {
+                merge(e, w);
+            }
+        }
         y.simplify();
         y
     }
@@ -279,12 +300,12 @@ where
 impl<E1, E2, W1, W2> Mul<WeightedSum<E2, W2>> for WeightedSum<E1, W1>
 where
     WeightedSum<<E1 as Mul<E2>>::Output, <W1 as Mul<W2>>::Output>: PartialZero + Simplify,
-    E1: Mul<E2> + Eq + Hash + Ord + Clone,
-    W1: Mul<W2> + Clone,
-    E2: Eq + Hash + Ord + Clone,
-    W2: Clone,
-    <E1 as Mul<E2>>::Output: Eq + Hash + Ord,
-    <W1 as Mul<W2>>::Output: AddAssign
+    E1: Mul<E2> + Eq + Hash + Ord + Clone + Sync,
+    W1: Mul<W2> + Clone + Sync,
+    E2: Eq + Hash + Ord + Clone + Sync,
+    W2: Clone + Sync,
+    <E1 as Mul<E2>>::Output: Eq + Hash + Ord + Send,
+    <W1 as Mul<W2>>::Output: AddAssign + Send
 {
     type Output = WeightedSum<<E1 as Mul<E2>>::Output, <W1 as Mul<W2>>::Output>;
 
@@ -392,4 +413,23 @@ where
             }
         }
     }
+}
+
+impl<E, W, F> Compute<F> for WeightedSum<E, W>
+where
+    E: Compute<F, Output = F> + Eq + Hash + Ord,
+    W: ToPrimitive + Clone,
+    F: Float
+{
+    type Output = F;
+
+    fn compute(&self, syms: HashMap<&'static str, F>) -> Option<Self::Output>
+    {
+        let mut y = F::zero();
+        for (e, w) in self.0.iter()
+        {
+            y = y + <F as NumCast>::from(w.clone())?*e.compute(syms.clone())?;
+        }
+        Some(y)
+    }
 }
\ No newline at end of file